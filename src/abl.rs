@@ -0,0 +1,39 @@
+/// Automatic brightness limiting and energy-saving settings of a [crate::Controller]
+///
+/// Obtained with [crate::Controller::abl_settings], bytes of the ABL block this crate
+/// does not decode are read again and kept untouched by [crate::Controller::set_abl_settings].
+/// So is the peak luminance of the screen held in the same block, which bounds the HDR settings
+/// and is only set through [crate::Controller::set_screen_peak_luminance]
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AblSettings {
+    /// Brightness is limited when the screen load exceeds the threshold
+    pub enabled: bool,
+    /// Pixels showing black are turned off to save energy
+    pub energy_saving: bool,
+    /// Screen load, in percent of full white, above which the brightness is limited
+    pub threshold: u8,
+    /// Lowest brightness, in percent, the limiting can go down to
+    pub min_level: u8,
+}
+
+impl AblSettings {
+    pub(crate) fn decode(data: &[u8]) -> Self {
+        Self {
+            threshold: data[0],
+            min_level: data[1],
+            energy_saving: data[4] != 0,
+            enabled: data[5] != 0,
+        }
+    }
+
+    /// Encodes the settings over `current`, the ABL block read from the controller
+    pub(crate) fn encode(&self, current: [u8; 16]) -> [u8; 16] {
+        let mut data = current;
+        data[0] = self.threshold;
+        data[1] = self.min_level;
+        data[4] = self.energy_saving as u8;
+        data[5] = self.enabled as u8;
+        data
+    }
+}
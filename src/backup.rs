@@ -0,0 +1,114 @@
+use num_enum::FromPrimitive;
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+use thiserror::Error;
+
+use crate::types::SenderCardType;
+
+const MAGIC: &[u8; 4] = b"NSCB";
+const VERSION: u8 = 1;
+
+/// Errors returned while reading or writing a [ConfigBackup] file
+#[derive(Error, Debug)]
+pub enum BackupError {
+    /// Reading or writing the file failed
+    #[error("backup file error: {0}")]
+    Io(#[from] io::Error),
+    /// The file is not a configuration backup
+    #[error("not a configuration backup file")]
+    Magic,
+    /// The file was written by an unsupported version of this crate
+    #[error("unsupported backup file version {0}")]
+    Version(u8),
+}
+
+/// Full copy of the sending and receiving card parameters of a [crate::Controller]
+///
+/// Obtained with [crate::Controller::backup] and applied with [crate::Controller::restore]
+#[derive(PartialEq, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ConfigBackup {
+    /// Raw model ID of the controller the backup was taken from
+    pub model_id: u16,
+    /// Serial number of the controller the backup was taken from
+    pub serial_number: u64,
+    /// Backup file header generated by the controller
+    pub header: Vec<u8>,
+    /// Sending card parameters
+    pub sender_parameters: Vec<u8>,
+    /// Receiving card parameters
+    pub led_parameters: Vec<u8>,
+}
+
+impl ConfigBackup {
+    /// Returns the model of the controller the backup was taken from
+    pub fn model(&self) -> SenderCardType {
+        SenderCardType::from_primitive(self.model_id)
+    }
+
+    /// Writes the backup in the versioned backup file format
+    pub fn write_to(&self, mut writer: impl Write) -> Result<(), BackupError> {
+        writer.write_all(MAGIC)?;
+        writer.write_all(&[VERSION])?;
+        writer.write_all(&self.model_id.to_le_bytes())?;
+        writer.write_all(&self.serial_number.to_le_bytes())?;
+        for block in [&self.header, &self.sender_parameters, &self.led_parameters] {
+            writer.write_all(&(block.len() as u32).to_le_bytes())?;
+            writer.write_all(block)?;
+        }
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Reads a backup written by [ConfigBackup::write_to]
+    pub fn read_from(mut reader: impl Read) -> Result<Self, BackupError> {
+        let mut magic = [0; 4];
+        reader.read_exact(&mut magic)?;
+        if &magic != MAGIC {
+            return Err(BackupError::Magic);
+        }
+
+        let mut version = [0; 1];
+        reader.read_exact(&mut version)?;
+        if version[0] != VERSION {
+            return Err(BackupError::Version(version[0]));
+        }
+
+        let mut model_id = [0; 2];
+        reader.read_exact(&mut model_id)?;
+        let mut serial_number = [0; 8];
+        reader.read_exact(&mut serial_number)?;
+
+        Ok(Self {
+            model_id: u16::from_le_bytes(model_id),
+            serial_number: u64::from_le_bytes(serial_number),
+            header: read_block(&mut reader)?,
+            sender_parameters: read_block(&mut reader)?,
+            led_parameters: read_block(&mut reader)?,
+        })
+    }
+
+    /// Saves the backup to the file at `path`
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), BackupError> {
+        self.write_to(BufWriter::new(File::create(path)?))
+    }
+
+    /// Loads a backup from the file at `path`
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, BackupError> {
+        Self::read_from(BufReader::new(File::open(path)?))
+    }
+}
+
+fn read_block(reader: &mut impl Read) -> Result<Vec<u8>, BackupError> {
+    let mut len = [0; 4];
+    reader.read_exact(&mut len)?;
+    let len = u32::from_le_bytes(len).into();
+    // The length is not trusted for the allocation, a truncated file ends the block early instead
+    let mut block = Vec::new();
+    reader.take(len).read_to_end(&mut block)?;
+    if (block.len() as u64) < len {
+        return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into());
+    }
+    Ok(block)
+}
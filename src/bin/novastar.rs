@@ -0,0 +1,464 @@
+//! Command-line tool for Novastar LED Screen processors
+
+use std::error::Error;
+use std::net::{IpAddr, SocketAddr};
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use clap::{Parser, Subcommand};
+use novastar_core::{
+    Controller, ControllerDescriptor, Target, TestPattern, Transport,
+    capture::{self, CapturedFrame, Protocol},
+    hex, net, serial,
+};
+use num_enum::TryFromPrimitive;
+use serde_json::{Map, Value, json};
+
+/// Serial speed tried first, as in [serial::discover]
+const FAST_BAUD_RATE: u32 = 1048576;
+/// Serial speed tried when the fast one fails
+const SLOW_BAUD_RATE: u32 = 115200;
+
+/// Discover, inspect and drive Novastar LED screen processors
+#[derive(Parser)]
+#[command(name = "novastar", version)]
+struct Cli {
+    /// Print the output as JSON instead of tables
+    #[arg(long, global = true)]
+    json: bool,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// List the controllers on the network and on the serial ports
+    Discover {
+        /// Only search the network
+        #[arg(long, conflicts_with = "serial")]
+        net: bool,
+        /// Only search the serial ports
+        #[arg(long)]
+        serial: bool,
+    },
+    /// Show the model, serial number and state of a controller
+    Info {
+        /// IP address, `IP:port` or serial port name of the controller
+        controller: String,
+    },
+    /// Read or set the global brightness
+    Brightness {
+        /// IP address, `IP:port` or serial port name of the controller
+        controller: String,
+        /// New brightness, from 0 to 255, the current one is printed when omitted
+        value: Option<u8>,
+    },
+    /// Read raw bytes from a register of the sending card
+    Read {
+        /// IP address, `IP:port` or serial port name of the controller
+        controller: String,
+        /// Register address, in hexadecimal with a `0x` prefix or in decimal
+        #[arg(value_parser = parse_number)]
+        address: u32,
+        /// Number of bytes to read
+        #[arg(default_value_t = 1)]
+        len: usize,
+    },
+    /// Write raw bytes to a register of the sending card
+    Write {
+        /// IP address, `IP:port` or serial port name of the controller
+        controller: String,
+        /// Register address, in hexadecimal with a `0x` prefix or in decimal
+        #[arg(value_parser = parse_number)]
+        address: u32,
+        /// Bytes to write, in hexadecimal, such as `01 ff`, `0x01 0xff` or `01ff`
+        #[arg(required = true, num_args = 1..)]
+        data: Vec<String>,
+    },
+    /// Show a test pattern, or `normal` to go back to the input video
+    TestPattern {
+        /// IP address, `IP:port` or serial port name of the controller
+        controller: String,
+        /// Pattern name, such as `red`, `grid` or `normal`
+        #[arg(value_parser = parse_pattern)]
+        pattern: TestPattern,
+        /// Only show the pattern on this output port
+        #[arg(long)]
+        port: Option<u8>,
+        /// Only show the pattern on this receiving card of `--port`
+        #[arg(long, requires = "port")]
+        card: Option<u16>,
+    },
+    /// Save the configuration of a controller to a file
+    Backup {
+        /// IP address, `IP:port` or serial port name of the controller
+        controller: String,
+        /// File the backup is written to
+        file: PathBuf,
+    },
+    /// Print the timeline of the Novastar traffic of a pcap or pcapng capture
+    Capture {
+        /// Capture file, as saved by Wireshark or tcpdump
+        file: PathBuf,
+    },
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    match run(cli.command, cli.json) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("novastar: {err}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(command: Command, json: bool) -> Result<(), Box<dyn Error>> {
+    match command {
+        Command::Discover { net, serial } => {
+            let mut controllers: Vec<Controller> = Vec::new();
+            if !serial {
+                controllers.extend(net::discover()?);
+            }
+            if !net {
+                controllers.extend(serial::discover()?);
+            }
+            let descriptors: Vec<ControllerDescriptor> =
+                controllers.iter().map(Controller::descriptor).collect();
+            if json {
+                print_json(&descriptors)?;
+            } else {
+                let rows = descriptors
+                    .iter()
+                    .map(|descriptor| {
+                        let (transport, address) = match &descriptor.transport {
+                            Transport::Tcp(addr) => ("tcp", addr.to_string()),
+                            Transport::Serial {
+                                port_name,
+                                baud_rate,
+                            } => ("serial", format!("{port_name}@{baud_rate}")),
+                        };
+                        vec![
+                            transport.to_string(),
+                            address,
+                            descriptor.model.to_string(),
+                            format!("{:#06x}", u16::from(descriptor.model)),
+                        ]
+                    })
+                    .collect();
+                print_table(&["TRANSPORT", "ADDRESS", "MODEL", "ID"], rows);
+            }
+        }
+        Command::Info { controller } => {
+            let mut controller = connect(&controller)?;
+            let capabilities = controller.capabilities();
+            let features: Vec<String> = capabilities
+                .features
+                .iter()
+                .map(ToString::to_string)
+                .collect();
+            let info = [
+                ("connection", json!(controller.connection().to_string())),
+                ("model", json!(controller.card_type().to_string())),
+                ("model_id", json!(format!("{:#06x}", controller.model_id()))),
+                (
+                    "serial_number",
+                    json!(supported(controller.serial_number())?),
+                ),
+                ("brightness", json!(supported(controller.brightness())?)),
+                (
+                    "display_state",
+                    json!(supported(controller.display_state())?.map(|state| state.to_string())),
+                ),
+                ("ethernet_ports", json!(capabilities.ethernet_ports)),
+                ("max_load_pixels", json!(capabilities.max_load_pixels)),
+                ("features", json!(features)),
+            ];
+            if json {
+                let object: Map<String, Value> = info
+                    .into_iter()
+                    .map(|(key, value)| (key.to_string(), value))
+                    .collect();
+                print_json(&object)?;
+            } else {
+                let rows = info
+                    .iter()
+                    .map(|(key, value)| vec![key.to_string(), plain(value)])
+                    .collect();
+                print_table(&["PROPERTY", "VALUE"], rows);
+            }
+        }
+        Command::Brightness { controller, value } => {
+            let mut controller = connect(&controller)?;
+            let brightness = match value {
+                Some(value) => {
+                    controller.set_brightness(value)?;
+                    value
+                }
+                None => controller.brightness()?,
+            };
+            if json {
+                print_json(&json!({ "brightness": brightness }))?;
+            } else {
+                println!("{brightness}");
+            }
+        }
+        Command::Read {
+            controller,
+            address,
+            len,
+        } => {
+            let data = connect(&controller)?.read_register(address, len)?;
+            if json {
+                print_json(&json!({ "address": address, "data": data }))?;
+            } else {
+                print!("{}", hexdump(address, &data));
+            }
+        }
+        Command::Write {
+            controller,
+            address,
+            data,
+        } => {
+            let digits: String = data
+                .iter()
+                .map(|bytes| strip_hex_prefix(bytes).unwrap_or(bytes))
+                .collect();
+            let data = parse_hex(&digits)?;
+            connect(&controller)?.write_register(address, &data)?;
+            if json {
+                print_json(&json!({ "address": address, "written": data.len() }))?;
+            } else {
+                println!("wrote {} bytes at {address:#010x}", data.len());
+            }
+        }
+        Command::TestPattern {
+            controller,
+            pattern,
+            port,
+            card,
+        } => {
+            let target = match (port, card) {
+                (Some(port), Some(card)) => Target::Card { port, card },
+                (Some(port), None) => Target::Port(port),
+                _ => Target::Screen,
+            };
+            connect(&controller)?.show_test_pattern(pattern, target)?;
+            if json {
+                print_json(&json!({ "pattern": pattern.to_string() }))?;
+            } else {
+                println!("showing {pattern}");
+            }
+        }
+        Command::Backup { controller, file } => {
+            let backup = connect(&controller)?.backup()?;
+            backup.save(&file)?;
+            if json {
+                print_json(&json!({
+                    "file": file,
+                    "model": backup.model().to_string(),
+                    "serial_number": backup.serial_number,
+                }))?;
+            } else {
+                println!("saved {} backup to {}", backup.model(), file.display());
+            }
+        }
+        Command::Capture { file } => {
+            let frames = capture::read_capture(&file)?;
+            let rows: Vec<Vec<String>> = frames.iter().map(timeline_row).collect();
+            if json {
+                let entries: Vec<Map<String, Value>> = rows
+                    .into_iter()
+                    .map(|row| {
+                        TIMELINE_HEADERS
+                            .iter()
+                            .map(|header| header.to_lowercase())
+                            .zip(row.into_iter().map(Value::String))
+                            .collect()
+                    })
+                    .collect();
+                print_json(&entries)?;
+            } else {
+                print_table(&TIMELINE_HEADERS, rows);
+            }
+        }
+    }
+    Ok(())
+}
+
+const TIMELINE_HEADERS: [&str; 7] = [
+    "TIME",
+    "SOURCE",
+    "DESTINATION",
+    "OP",
+    "ADDRESS",
+    "LEN",
+    "DATA",
+];
+
+/// Returns the timeline columns of a captured frame
+fn timeline_row(frame: &CapturedFrame) -> Vec<String> {
+    let time = format!(
+        "{}.{:06}",
+        frame.timestamp.as_secs(),
+        frame.timestamp.subsec_micros()
+    );
+    let (op, address, len, data) = match frame.packet() {
+        Ok(packet) => (
+            format!("{:?}", packet.op_code),
+            packet
+                .address_name()
+                .unwrap_or_else(|| format!("{:#010x}", packet.address)),
+            packet.data_len.to_string(),
+            hex(packet.data),
+        ),
+        Err(_) if frame.protocol == Protocol::Udp => (
+            "Discovery".to_string(),
+            String::new(),
+            frame.bytes.len().to_string(),
+            hex(&frame.bytes),
+        ),
+        Err(err) => (
+            "Invalid".to_string(),
+            err.to_string(),
+            frame.bytes.len().to_string(),
+            hex(&frame.bytes),
+        ),
+    };
+    vec![
+        time,
+        frame.source.to_string(),
+        frame.destination.to_string(),
+        op,
+        address,
+        len,
+        data,
+    ]
+}
+
+/// Returns the value of a query, or `None` when the controller does not support it
+fn supported<T>(
+    result: Result<T, novastar_core::Error>,
+) -> Result<Option<T>, novastar_core::Error> {
+    match result {
+        Ok(value) => Ok(Some(value)),
+        Err(novastar_core::Error::Unsupported(_)) => Ok(None),
+        Err(err) => Err(err),
+    }
+}
+
+/// Connects to `controller`, given as an IP address, an `IP:port` pair or a serial port name
+fn connect(controller: &str) -> Result<Controller, Box<dyn Error>> {
+    if let Ok(addr) = controller.parse::<SocketAddr>() {
+        return Ok(Controller::try_from_tcp_addr(addr)?);
+    }
+    if let Ok(ip) = controller.parse::<IpAddr>() {
+        let addr = SocketAddr::new(ip, capture::CONTROLLER_PORT);
+        return Ok(Controller::try_from_tcp_addr(addr)?);
+    }
+    serial::try_com_connect(controller, FAST_BAUD_RATE)
+        .or_else(|_| serial::try_com_connect(controller, SLOW_BAUD_RATE))?
+        .ok_or_else(|| format!("no controller answered on {controller}").into())
+}
+
+/// Returns the digits of `value` after its `0x` or `0X` prefix, `None` when it has none
+fn strip_hex_prefix(value: &str) -> Option<&str> {
+    value
+        .strip_prefix("0x")
+        .or_else(|| value.strip_prefix("0X"))
+}
+
+fn parse_number(value: &str) -> Result<u32, String> {
+    match strip_hex_prefix(value) {
+        Some(hex) => u32::from_str_radix(hex, 16),
+        None => value.parse(),
+    }
+    .map_err(|err| err.to_string())
+}
+
+fn parse_hex(value: &str) -> Result<Vec<u8>, String> {
+    if !value.is_ascii() {
+        return Err(format!("invalid hexadecimal digits in {value:?}"));
+    }
+    if !value.len().is_multiple_of(2) {
+        return Err(format!("odd number of hexadecimal digits in {value:?}"));
+    }
+    (0..value.len())
+        .step_by(2)
+        .map(|i| {
+            u8::from_str_radix(&value[i..i + 2], 16)
+                .map_err(|_| format!("invalid hexadecimal byte {:?}", &value[i..i + 2]))
+        })
+        .collect()
+}
+
+fn parse_pattern(value: &str) -> Result<TestPattern, String> {
+    let patterns = || (0..=u8::MAX).filter_map(|id| TestPattern::try_from_primitive(id).ok());
+    patterns()
+        .find(|pattern| pattern.to_string().eq_ignore_ascii_case(value))
+        .ok_or_else(|| {
+            let names: Vec<String> = patterns().map(|pattern| pattern.to_string()).collect();
+            format!("expected one of {}", names.join(", "))
+        })
+}
+
+/// Formats `data` as lines of 16 bytes, prefixed by their register address
+fn hexdump(address: u32, data: &[u8]) -> String {
+    let mut out = String::new();
+    for (line, chunk) in data.chunks(16).enumerate() {
+        let bytes: Vec<String> = chunk.iter().map(|byte| format!("{byte:02x}")).collect();
+        let text: String = chunk
+            .iter()
+            .map(|&byte| {
+                if byte.is_ascii_graphic() || byte == b' ' {
+                    byte as char
+                } else {
+                    '.'
+                }
+            })
+            .collect();
+        out += &format!(
+            "{:#010x}  {:<47}  |{text}|\n",
+            address.wrapping_add(16 * line as u32),
+            bytes.join(" ")
+        );
+    }
+    out
+}
+
+fn print_json(value: &impl serde::Serialize) -> Result<(), serde_json::Error> {
+    println!("{}", serde_json::to_string_pretty(value)?);
+    Ok(())
+}
+
+/// Formats a JSON value for a table cell, without the quotes around strings
+fn plain(value: &Value) -> String {
+    match value {
+        Value::String(text) => text.clone(),
+        Value::Array(items) => items.iter().map(plain).collect::<Vec<_>>().join(", "),
+        Value::Null => "unknown".to_string(),
+        other => other.to_string(),
+    }
+}
+
+fn print_table(headers: &[&str], rows: Vec<Vec<String>>) {
+    let mut widths: Vec<usize> = headers.iter().map(|header| header.len()).collect();
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.len());
+        }
+    }
+    let line = |cells: Vec<&str>| {
+        let cells: Vec<String> = cells
+            .iter()
+            .zip(&widths)
+            .map(|(cell, width)| format!("{cell:<width$}"))
+            .collect();
+        println!("{}", cells.join("  ").trim_end());
+    };
+    line(headers.to_vec());
+    for row in &rows {
+        line(row.iter().map(String::as_str).collect());
+    }
+}
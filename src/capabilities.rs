@@ -0,0 +1,170 @@
+use strum_macros::Display;
+
+use crate::types::SenderCardType;
+
+/// Optional feature of a controller
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Display)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Feature {
+    /// HDR10 and HLG tone mapping
+    Hdr,
+    /// Locking the output to a genlock input
+    Genlock,
+    /// Stereoscopic 3D output
+    ThreeD,
+    /// Automatic brightness limiting and energy saving
+    Abl,
+    /// Scaling, cropping and window placement of the input
+    VideoProcessing,
+    /// Master and backup controllers and port hot backup
+    Redundancy,
+    /// Configuration backup and restore
+    Backup,
+    /// Reading and writing registers through the binary protocol
+    RegisterAccess,
+}
+
+/// Protocol used to drive a controller
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ProtocolFamily {
+    /// The binary protocol, over TCP port 5200 or a serial port
+    Binary,
+    /// The binary protocol for discovery, and the web API for screen settings
+    HSeriesHttp,
+}
+
+/// Address family holding the per-port registers, which depends on the number of ports
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PortAddressFamily {
+    /// Up to 4 ports
+    Legacy,
+    /// Up to 8 ports
+    New,
+    /// Up to 16 ports
+    New16,
+    /// Up to 32 ports
+    New32,
+}
+
+/// Hardware capabilities of a controller model
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Capabilities {
+    /// Number of Ethernet output ports, `None` when it depends on the installed output cards
+    /// or the model is unknown
+    pub ethernet_ports: Option<u8>,
+    /// Number of pixels the controller can drive, `None` when it depends on the installed output
+    /// cards or the model is unknown
+    pub max_load_pixels: Option<u32>,
+    /// Optional features supported by the controller
+    pub features: &'static [Feature],
+    /// Protocol used to drive the controller
+    pub protocol: ProtocolFamily,
+}
+
+impl Capabilities {
+    /// Returns `true` when the controller supports `feature`
+    ///
+    /// [Feature::RegisterAccess] is supported by every controller driven through the binary protocol
+    pub fn supports(&self, feature: Feature) -> bool {
+        match feature {
+            Feature::RegisterAccess => self.protocol == ProtocolFamily::Binary,
+            _ => self.features.contains(&feature),
+        }
+    }
+
+    /// Returns the address family of the per-port registers, `None` when the number of ports is unknown
+    pub fn port_address_family(&self) -> Option<PortAddressFamily> {
+        self.ethernet_ports.map(|ports| match ports {
+            0..=4 => PortAddressFamily::Legacy,
+            5..=8 => PortAddressFamily::New,
+            9..=16 => PortAddressFamily::New16,
+            _ => PortAddressFamily::New32,
+        })
+    }
+}
+
+const ALL: &[Feature] = &[
+    Feature::Hdr,
+    Feature::Genlock,
+    Feature::ThreeD,
+    Feature::Abl,
+    Feature::VideoProcessing,
+    Feature::Redundancy,
+    Feature::Backup,
+];
+const SENDING_CARD: &[Feature] = &[Feature::Abl, Feature::Redundancy, Feature::Backup];
+const VIDEO_CONTROLLER: &[Feature] = &[
+    Feature::Abl,
+    Feature::VideoProcessing,
+    Feature::Redundancy,
+    Feature::Backup,
+];
+const BROADCAST_CONTROLLER: &[Feature] = &[
+    Feature::Genlock,
+    Feature::ThreeD,
+    Feature::Abl,
+    Feature::VideoProcessing,
+    Feature::Redundancy,
+    Feature::Backup,
+];
+
+const fn binary(
+    ethernet_ports: u8,
+    max_load_pixels: u32,
+    features: &'static [Feature],
+) -> Capabilities {
+    Capabilities {
+        ethernet_ports: Some(ethernet_ports),
+        max_load_pixels: Some(max_load_pixels),
+        features,
+        protocol: ProtocolFamily::Binary,
+    }
+}
+
+impl SenderCardType {
+    /// Returns the hardware capabilities of this model
+    ///
+    /// Models this crate has no data about are only given register access, with no optional
+    /// feature and no known number of ports
+    pub fn capabilities(&self) -> Capabilities {
+        match self {
+            SenderCardType::MCTRL300 => binary(2, 1_300_000, &[Feature::Backup]),
+            SenderCardType::MCTRL500 => binary(4, 2_300_000, &[Feature::Backup]),
+            SenderCardType::MCTRL600_660 | SenderCardType::MCTRL660_ROE => {
+                binary(4, 2_300_000, SENDING_CARD)
+            }
+            SenderCardType::MCTRL660_Pro => binary(6, 3_900_000, BROADCAST_CONTROLLER),
+            SenderCardType::MCTRL700 => binary(6, 2_300_000, SENDING_CARD),
+            SenderCardType::MCTRL4K => binary(16, 8_800_000, ALL),
+            SenderCardType::K4S_N | SenderCardType::VX4S_N => {
+                binary(4, 2_300_000, VIDEO_CONTROLLER)
+            }
+            SenderCardType::VX200s_N => binary(2, 1_300_000, VIDEO_CONTROLLER),
+            SenderCardType::VX400Pro => binary(4, 2_600_000, VIDEO_CONTROLLER),
+            SenderCardType::VX600Pro | SenderCardType::VC6Pro => {
+                binary(6, 3_900_000, BROADCAST_CONTROLLER)
+            }
+            SenderCardType::VX1000Pro | SenderCardType::VC10Pro => {
+                binary(10, 6_500_000, BROADCAST_CONTROLLER)
+            }
+            SenderCardType::VX2000Pro => binary(20, 13_000_000, ALL),
+            SenderCardType::NovaProUHD_Jr => binary(16, 10_400_000, ALL),
+            SenderCardType::NovaProUHD | SenderCardType::Prime_4K => binary(16, 8_800_000, ALL),
+            SenderCardType::H_series_H9 | SenderCardType::H_series_H20 => Capabilities {
+                ethernet_ports: None,
+                max_load_pixels: None,
+                features: &[],
+                protocol: ProtocolFamily::HSeriesHttp,
+            },
+            _ => Capabilities {
+                ethernet_ports: None,
+                max_load_pixels: None,
+                features: &[],
+                protocol: ProtocolFamily::Binary,
+            },
+        }
+    }
+}
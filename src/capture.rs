@@ -0,0 +1,435 @@
+//! This module reads the Novastar traffic out of pcap and pcapng captures, such as the ones taken
+//! with Wireshark while NovaLCT drives a controller
+
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::fs;
+use std::io;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::path::Path;
+use std::time::Duration;
+
+use thiserror::Error;
+
+use crate::novastarpacket::{NovastarPacket, PacketError, REQUEST, RESPONSE, frame_len, hex};
+
+/// TCP port of the binary protocol
+pub const CONTROLLER_PORT: u16 = 5200;
+/// UDP port of the discovery protocol
+pub const DISCOVERY_PORT: u16 = 3800;
+
+/// Errors returned while reading a capture
+#[derive(Error, Debug)]
+pub enum CaptureError {
+    /// Reading the file failed
+    #[error("capture file error: {0}")]
+    Io(#[from] io::Error),
+    /// The file is neither a pcap nor a pcapng capture
+    #[error("not a pcap or pcapng capture")]
+    Format,
+    /// The capture ends in the middle of a record
+    #[error("truncated capture")]
+    Truncated,
+}
+
+/// Transport protocol a [CapturedFrame] was carried by
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum Protocol {
+    /// Binary protocol stream, on [CONTROLLER_PORT]
+    Tcp,
+    /// Discovery datagram, on [DISCOVERY_PORT]
+    Udp,
+}
+
+/// Novastar frame or discovery datagram found in a capture
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct CapturedFrame {
+    /// Capture time of the packet completing the frame, since the Unix epoch
+    pub timestamp: Duration,
+    /// Transport protocol
+    pub protocol: Protocol,
+    /// Sender of the frame
+    pub source: SocketAddr,
+    /// Receiver of the frame
+    pub destination: SocketAddr,
+    /// Bytes of the frame
+    pub bytes: Vec<u8>,
+}
+
+impl CapturedFrame {
+    /// Decodes the frame without rejecting a bad checksum, discovery datagrams are not binary
+    /// protocol frames and fail to decode
+    pub fn packet(&self) -> Result<NovastarPacket<'_>, PacketError> {
+        NovastarPacket::parse(&self.bytes)
+    }
+}
+
+impl Display for CapturedFrame {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}.{:06} {} -> {} ",
+            self.timestamp.as_secs(),
+            self.timestamp.subsec_micros(),
+            self.source,
+            self.destination
+        )?;
+        match (self.protocol, self.packet()) {
+            (_, Ok(packet)) => write!(f, "{packet}"),
+            (Protocol::Udp, Err(_)) => write!(f, "discovery [{}]", hex(&self.bytes)),
+            (Protocol::Tcp, Err(err)) => write!(f, "{err} [{}]", hex(&self.bytes)),
+        }
+    }
+}
+
+/// Reads the Novastar frames of the pcap or pcapng capture at `path`, in capture order
+pub fn read_capture(path: impl AsRef<Path>) -> Result<Vec<CapturedFrame>, CaptureError> {
+    parse_capture(&fs::read(path)?)
+}
+
+/// Reads the Novastar frames of a pcap or pcapng capture held in memory, in capture order
+///
+/// TCP streams on [CONTROLLER_PORT] are reassembled before being split into frames, UDP
+/// datagrams on [DISCOVERY_PORT] are returned as they are
+pub fn parse_capture(capture: &[u8]) -> Result<Vec<CapturedFrame>, CaptureError> {
+    let mut streams = Streams::default();
+    match capture.get(0..4) {
+        Some([0x0A, 0x0D, 0x0D, 0x0A]) => read_pcapng(capture, &mut streams)?,
+        Some(_) => read_pcap(capture, &mut streams)?,
+        None => return Err(CaptureError::Format),
+    }
+    Ok(streams.frames)
+}
+
+/// Integer reader following the byte order of the capture
+#[derive(Clone, Copy)]
+struct Endian {
+    big: bool,
+}
+
+impl Endian {
+    fn u16(self, buff: &[u8], at: usize) -> Result<u16, CaptureError> {
+        let bytes = buff
+            .get(at..at + 2)
+            .ok_or(CaptureError::Truncated)?
+            .try_into()
+            .expect("two bytes");
+        Ok(if self.big {
+            u16::from_be_bytes(bytes)
+        } else {
+            u16::from_le_bytes(bytes)
+        })
+    }
+
+    fn u32(self, buff: &[u8], at: usize) -> Result<u32, CaptureError> {
+        let bytes = buff
+            .get(at..at + 4)
+            .ok_or(CaptureError::Truncated)?
+            .try_into()
+            .expect("four bytes");
+        Ok(if self.big {
+            u32::from_be_bytes(bytes)
+        } else {
+            u32::from_le_bytes(bytes)
+        })
+    }
+}
+
+fn read_pcap(capture: &[u8], streams: &mut Streams) -> Result<(), CaptureError> {
+    let (endian, nanos) = match capture[0..4] {
+        [0xD4, 0xC3, 0xB2, 0xA1] => (Endian { big: false }, false),
+        [0xA1, 0xB2, 0xC3, 0xD4] => (Endian { big: true }, false),
+        [0x4D, 0x3C, 0xB2, 0xA1] => (Endian { big: false }, true),
+        [0xA1, 0xB2, 0x3C, 0x4D] => (Endian { big: true }, true),
+        _ => return Err(CaptureError::Format),
+    };
+    let link_type = endian.u32(capture, 20)?;
+
+    let mut at = 24;
+    while at < capture.len() {
+        let seconds = endian.u32(capture, at)?;
+        let fraction = endian.u32(capture, at + 4)?;
+        let len = endian.u32(capture, at + 8)? as usize;
+        let data = capture
+            .get(at + 16..at + 16 + len)
+            .ok_or(CaptureError::Truncated)?;
+        let timestamp = Duration::from_secs(seconds.into())
+            + if nanos {
+                Duration::from_nanos(fraction.into())
+            } else {
+                Duration::from_micros(fraction.into())
+            };
+        streams.push_link(timestamp, link_type, data);
+        at += 16 + len;
+    }
+    Ok(())
+}
+
+/// Link type and timestamp unit of a pcapng interface
+struct Interface {
+    link_type: u32,
+    ticks_per_second: u64,
+}
+
+fn read_pcapng(capture: &[u8], streams: &mut Streams) -> Result<(), CaptureError> {
+    let mut endian = Endian { big: false };
+    let mut interfaces: Vec<Interface> = Vec::new();
+
+    let mut at = 0;
+    while at < capture.len() {
+        let block_type = endian.u32(capture, at)?;
+        if block_type == 0x0A0D_0D0A {
+            // Each section header sets the byte order of the blocks which follow it
+            endian = match capture.get(at + 8..at + 12) {
+                Some([0x4D, 0x3C, 0x2B, 0x1A]) => Endian { big: false },
+                Some([0x1A, 0x2B, 0x3C, 0x4D]) => Endian { big: true },
+                _ => return Err(CaptureError::Format),
+            };
+            interfaces.clear();
+        }
+        let len = endian.u32(capture, at + 4)? as usize;
+        if len < 12 {
+            return Err(CaptureError::Format);
+        }
+        let body = capture
+            .get(at + 8..at + len - 4)
+            .ok_or(CaptureError::Truncated)?;
+
+        match block_type {
+            // Interface description
+            0x0000_0001 => interfaces.push(Interface {
+                link_type: endian.u16(body, 0)?.into(),
+                ticks_per_second: ticks_per_second(endian, body.get(8..).unwrap_or_default())?,
+            }),
+            // Enhanced packet
+            0x0000_0006 => {
+                let interface = interfaces
+                    .get(endian.u32(body, 0)? as usize)
+                    .ok_or(CaptureError::Format)?;
+                let ticks =
+                    (u64::from(endian.u32(body, 4)?) << 32) | u64::from(endian.u32(body, 8)?);
+                let captured_len = endian.u32(body, 12)? as usize;
+                let data = body
+                    .get(20..20 + captured_len)
+                    .ok_or(CaptureError::Truncated)?;
+                let timestamp = Duration::from_secs(ticks / interface.ticks_per_second)
+                    + Duration::from_nanos(
+                        (u128::from(ticks % interface.ticks_per_second) * 1_000_000_000
+                            / u128::from(interface.ticks_per_second))
+                            as u64,
+                    );
+                streams.push_link(timestamp, interface.link_type, data);
+            }
+            // Simple packet, without timestamp
+            0x0000_0003 => {
+                let interface = interfaces.first().ok_or(CaptureError::Format)?;
+                let original_len = endian.u32(body, 0)? as usize;
+                let data = &body[4..(4 + original_len).min(body.len())];
+                streams.push_link(Duration::ZERO, interface.link_type, data);
+            }
+            _ => {}
+        }
+        at += len;
+    }
+    Ok(())
+}
+
+/// Returns the timestamp unit of an interface, from its `if_tsresol` option
+fn ticks_per_second(endian: Endian, mut options: &[u8]) -> Result<u64, CaptureError> {
+    while options.len() >= 4 {
+        let code = endian.u16(options, 0)?;
+        let len = endian.u16(options, 2)? as usize;
+        if code == 0 {
+            break;
+        }
+        if code == 9 && len == 1 {
+            let resolution = *options.get(4).ok_or(CaptureError::Truncated)?;
+            let exponent = u32::from(resolution & 0x7F);
+            return if resolution & 0x80 == 0 {
+                10u64.checked_pow(exponent)
+            } else {
+                1u64.checked_shl(exponent)
+            }
+            .ok_or(CaptureError::Format);
+        }
+        options = options.get(4 + len.div_ceil(4) * 4..).unwrap_or_default();
+    }
+    Ok(1_000_000)
+}
+
+/// Bytes received on one direction of a TCP connexion, not yet split into frames
+#[derive(Default)]
+struct Stream {
+    next_seq: Option<u32>,
+    pending: Vec<u8>,
+    out_of_order: HashMap<u32, Vec<u8>>,
+}
+
+#[derive(Default)]
+struct Streams {
+    tcp: HashMap<(SocketAddr, SocketAddr), Stream>,
+    frames: Vec<CapturedFrame>,
+}
+
+impl Streams {
+    /// Strips the link layer header of a captured packet
+    fn push_link(&mut self, timestamp: Duration, link_type: u32, data: &[u8]) {
+        let network = match link_type {
+            // Ethernet, skipping 802.1Q tags
+            1 => {
+                let mut at = 12;
+                while data.get(at..at + 2) == Some(&[0x81, 0x00]) {
+                    at += 4;
+                }
+                data.get(at + 2..)
+            }
+            // BSD loopback, the IP version is read from the IP header instead of the address family
+            0 => data.get(4..),
+            // Raw IP
+            12 | 14 | 101 | 228 | 229 => Some(data),
+            // Linux cooked capture v1 and v2
+            113 => data.get(16..),
+            276 => data.get(20..),
+            _ => None,
+        };
+        if let Some(network) = network {
+            self.push_ip(timestamp, network);
+        }
+    }
+
+    fn push_ip(&mut self, timestamp: Duration, data: &[u8]) {
+        let Some(version) = data.first().map(|byte| byte >> 4) else {
+            return;
+        };
+        let (source, destination, protocol, payload) = match version {
+            4 if data.len() >= 20 => {
+                let header_len = (data[0] & 0x0F) as usize * 4;
+                if header_len < 20 || header_len > data.len() {
+                    return;
+                }
+                let total_len = u16::from_be_bytes([data[2], data[3]]) as usize;
+                let source = Ipv4Addr::new(data[12], data[13], data[14], data[15]);
+                let destination = Ipv4Addr::new(data[16], data[17], data[18], data[19]);
+                let end = total_len.clamp(header_len, data.len());
+                (
+                    IpAddr::V4(source),
+                    IpAddr::V4(destination),
+                    data[9],
+                    data.get(header_len..end),
+                )
+            }
+            6 if data.len() >= 40 => {
+                let payload_len = u16::from_be_bytes([data[4], data[5]]) as usize;
+                let source: [u8; 16] = data[8..24].try_into().expect("sixteen bytes");
+                let destination: [u8; 16] = data[24..40].try_into().expect("sixteen bytes");
+                (
+                    IpAddr::V6(Ipv6Addr::from(source)),
+                    IpAddr::V6(Ipv6Addr::from(destination)),
+                    data[6],
+                    data.get(40..(40 + payload_len).min(data.len())),
+                )
+            }
+            _ => return,
+        };
+        match (protocol, payload) {
+            (6, Some(segment)) => self.push_tcp(timestamp, source, destination, segment),
+            (17, Some(datagram)) => self.push_udp(timestamp, source, destination, datagram),
+            _ => {}
+        }
+    }
+
+    fn push_udp(&mut self, timestamp: Duration, source: IpAddr, destination: IpAddr, data: &[u8]) {
+        if data.len() < 8 {
+            return;
+        }
+        let source_port = u16::from_be_bytes([data[0], data[1]]);
+        let destination_port = u16::from_be_bytes([data[2], data[3]]);
+        if source_port != DISCOVERY_PORT && destination_port != DISCOVERY_PORT {
+            return;
+        }
+        let len = (u16::from_be_bytes([data[4], data[5]]) as usize).clamp(8, data.len());
+        self.frames.push(CapturedFrame {
+            timestamp,
+            protocol: Protocol::Udp,
+            source: SocketAddr::new(source, source_port),
+            destination: SocketAddr::new(destination, destination_port),
+            bytes: data[8..len].to_vec(),
+        });
+    }
+
+    fn push_tcp(&mut self, timestamp: Duration, source: IpAddr, destination: IpAddr, data: &[u8]) {
+        if data.len() < 20 {
+            return;
+        }
+        let source = SocketAddr::new(source, u16::from_be_bytes([data[0], data[1]]));
+        let destination = SocketAddr::new(destination, u16::from_be_bytes([data[2], data[3]]));
+        if source.port() != CONTROLLER_PORT && destination.port() != CONTROLLER_PORT {
+            return;
+        }
+        let seq = u32::from_be_bytes([data[4], data[5], data[6], data[7]]);
+        let header_len = (data[12] >> 4) as usize * 4;
+        let syn = data[13] & 0x02 != 0;
+        let payload = data.get(header_len..).unwrap_or_default();
+
+        let stream = self.tcp.entry((source, destination)).or_default();
+        if syn {
+            *stream = Stream {
+                next_seq: Some(seq.wrapping_add(1)),
+                ..Stream::default()
+            };
+            return;
+        }
+        if payload.is_empty() {
+            return;
+        }
+        let next_seq = *stream.next_seq.get_or_insert(seq);
+        // Relative position of the segment in the stream, negative for retransmitted bytes
+        let offset = seq.wrapping_sub(next_seq) as i32;
+        if offset > 0 {
+            stream.out_of_order.insert(seq, payload.to_vec());
+            return;
+        }
+        let Some(new_bytes) = payload.get(offset.unsigned_abs() as usize..) else {
+            return;
+        };
+        stream.pending.extend_from_slice(new_bytes);
+        let mut next_seq = next_seq.wrapping_add(new_bytes.len() as u32);
+        while let Some(segment) = stream.out_of_order.remove(&next_seq) {
+            stream.pending.extend_from_slice(&segment);
+            next_seq = next_seq.wrapping_add(segment.len() as u32);
+        }
+        stream.next_seq = Some(next_seq);
+
+        for bytes in split_frames(&mut stream.pending) {
+            self.frames.push(CapturedFrame {
+                timestamp,
+                protocol: Protocol::Tcp,
+                source,
+                destination,
+                bytes,
+            });
+        }
+    }
+}
+
+/// Takes the complete frames out of the start of `pending`, skipping bytes which cannot start one
+fn split_frames(pending: &mut Vec<u8>) -> Vec<Vec<u8>> {
+    let mut frames = Vec::new();
+    loop {
+        let start = pending.windows(2).position(|magic| {
+            let direction = u16::from_be_bytes([magic[0], magic[1]]);
+            direction == REQUEST || direction == RESPONSE
+        });
+        let Some(start) = start else {
+            // Keep a last byte which may be the first half of a magic number
+            let keep = pending.len().min(1);
+            pending.drain(..pending.len() - keep);
+            return frames;
+        };
+        pending.drain(..start);
+        match frame_len(pending) {
+            Some(len) if pending.len() >= len => frames.push(pending.drain(..len).collect()),
+            _ => return frames,
+        }
+    }
+}
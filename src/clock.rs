@@ -0,0 +1,45 @@
+use chrono::{Datelike, Local, NaiveDate, NaiveDateTime, TimeDelta, Timelike};
+
+use crate::controller::{Controller, Error};
+
+/// Decodes the real-time clock of a controller: years since 2000, month, day, hours,
+/// minutes and seconds
+pub(crate) fn decode(data: &[u8]) -> Option<NaiveDateTime> {
+    NaiveDate::from_ymd_opt(2000 + data[0] as i32, data[1].into(), data[2].into())?.and_hms_opt(
+        data[3].into(),
+        data[4].into(),
+        data[5].into(),
+    )
+}
+
+/// Encodes a date-time for the real-time clock of a controller, `None` when its year is not
+/// between 2000 and 2255
+pub(crate) fn encode(time: NaiveDateTime) -> Option<[u8; 6]> {
+    Some([
+        u8::try_from(time.year() - 2000).ok()?,
+        time.month() as u8,
+        time.day() as u8,
+        time.hour() as u8,
+        time.minute() as u8,
+        time.second() as u8,
+    ])
+}
+
+/// Returns the drift of the clock of each controller relative to the local time of this host,
+/// in the order of `controllers`
+///
+/// A positive drift means the controller clock is ahead
+pub fn clock_drifts<'a>(
+    controllers: impl IntoIterator<Item = &'a mut Controller>,
+) -> Vec<Result<TimeDelta, Error>> {
+    controllers
+        .into_iter()
+        .map(|controller| controller.clock_drift())
+        .collect()
+}
+
+/// Returns the local time of this host, truncated to the second like the controller clocks
+pub(crate) fn now() -> NaiveDateTime {
+    let now = Local::now().naive_local();
+    now.with_nanosecond(0).unwrap_or(now)
+}
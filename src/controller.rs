@@ -1,34 +1,149 @@
-use num_enum::TryFromPrimitive;
-use serialport::SerialPort;
+use chrono::{Datelike, NaiveDateTime, TimeDelta};
+use num_enum::{FromPrimitive, TryFromPrimitive};
+use serialport::{ClearBuffer, SerialPort};
 use std::fmt::Display;
 use std::io;
 use std::io::Read;
 use std::io::Write;
 use std::net::SocketAddr;
 use std::net::TcpStream;
-use std::time::Duration;
+use std::thread;
+use std::time::{Duration, Instant};
 use thiserror::Error;
+use tracing::{Span, debug, debug_span, field, info_span};
 
+use crate::abl::AblSettings;
+use crate::backup::ConfigBackup;
+use crate::capabilities::{Capabilities, Feature, PortAddressFamily};
+use crate::descriptor::{ControllerDescriptor, Transport};
+use crate::display::{CabinetRotation, MirrorMode, ThreeDSettings};
+use crate::edid::{EDID_LEN, Edid, EdidError};
+#[cfg(feature = "h-series")]
+use crate::fade::HTTP_FADE_STEP;
+use crate::fade::{CancelFade, FADE_STEP, FadeCurve};
+#[cfg(feature = "h-series")]
+use crate::h_series::{HSeriesClient, HSeriesError};
+use crate::hdr::HdrSettings;
 use crate::novastarpacket::*;
+use crate::redundancy::*;
+use crate::retry::RetryPolicy;
+use crate::sync::{FieldRateMode, MAX_FIELD_RATE, SyncSettings};
 use crate::types::*;
+use crate::video::*;
 
+/// Errors returned while talking to a [Controller]
 #[derive(Error, Debug)]
 pub enum Error {
+    /// Sending a packet to the controller failed
     #[error("write error: {0}")]
     Write(std::io::Error),
+    /// Receiving a packet from the controller failed
     #[error("read error: {0}")]
     Read(std::io::Error),
+    /// Flushing the connexion failed
     #[error("flush error: {0}")]
     Flush(std::io::Error),
+    /// The controller answered with a malformed packet
     #[error("invalid packet: {0}")]
     PacketDecoding(#[from] PacketError),
+    /// Opening the connexion to the controller failed
     #[error("Failed to connect: {0}")]
-    Connection(io::Error)
+    Connection(io::Error),
+    /// The EDID stored on the controller is invalid
+    #[error("invalid EDID: {0}")]
+    Edid(#[from] EdidError),
+    /// The controller has no output port with that index, or the port is already backed up
+    #[error("output port {0} does not exist on this controller or is already paired")]
+    InvalidPort(u8),
+    /// The controller returned a value this crate does not know about
+    #[error("unexpected value {0:#04x} read from the controller")]
+    UnexpectedValue(u32),
+    /// A setting is beyond what the controller or the screen supports
+    #[error("{value} exceeds the maximum of {max}")]
+    OutOfRange {
+        /// Requested value
+        value: u32,
+        /// Highest accepted value
+        max: u32,
+    },
+    /// A field rate cannot be encoded, as it is not between 0 and 655.35 Hz
+    #[error("invalid field rate {0} Hz, expected 0 to 655.35 Hz")]
+    InvalidFieldRate(f32),
+    /// The real-time clock of the controller holds an invalid date
+    #[error("invalid controller clock {0:?}")]
+    InvalidClock(Vec<u8>),
+    /// The real-time clock of the controller only holds years from 2000 to 2255
+    #[error("year {0} cannot be set on the controller clock, expected 2000 to 2255")]
+    InvalidClockYear(i32),
+    /// The web API of an H Series controller failed
+    #[cfg(feature = "h-series")]
+    #[error("H Series error: {0}")]
+    HSeries(#[from] HSeriesError),
+    /// The controller model does not support the feature
+    #[error("{0} is not supported by this controller")]
+    Unsupported(Feature),
+    /// A single packet cannot carry that many bytes
+    #[error("payload of {0} bytes is too large for a single packet")]
+    PayloadTooLarge(usize),
+    /// The controller did not report the end of an operation in time
+    #[error("operation did not complete within {0:?}")]
+    Timeout(Duration),
+    /// A backup taken from another model was restored on this controller
+    #[error("backup of model ID {backup:#06x} cannot be restored on model ID {controller:#06x}")]
+    ModelMismatch {
+        /// Model ID stored in the backup
+        backup: u16,
+        /// Model ID of this controller
+        controller: u16,
+    },
+    /// Another model answered on the address of the controller after a reconnection, or on the
+    /// address of a [crate::ControllerDescriptor]
+    #[error("expected model ID {expected:#06x} after reconnecting, found {found:#06x}")]
+    ModelChanged {
+        /// Model ID of this controller
+        expected: u16,
+        /// Model ID reported on the new connexion
+        found: u16,
+    },
+    /// The controller answered a read with fewer bytes than requested
+    #[error("expected {expected} bytes from the controller, received {received}")]
+    ShortReply {
+        /// Number of bytes requested
+        expected: usize,
+        /// Number of bytes received
+        received: usize,
+    },
+    /// The operation panicked while a [crate::ControllerGroup] ran it on this controller
+    #[error("operation panicked")]
+    Panicked,
 }
 
+impl Error {
+    /// Returns whether the connexion may be left out of step with the controller, and must be reopened
+    pub fn is_connection_error(&self) -> bool {
+        matches!(
+            self,
+            Error::Write(_) | Error::Read(_) | Error::Flush(_) | Error::PacketDecoding(_)
+        )
+    }
+}
+
+/// How long the controller is given to complete a backup or a restore
+const BACKUP_TIMEOUT: Duration = Duration::from_secs(30);
+/// Largest number of receiving card parameter bytes transferred by a single request of a backup
+/// or a restore
+const LED_PARAM_CHUNK: usize = 0x8000;
+/// Delay between two reads of a completion flag
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+/// How long opening a network connexion may take
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Transport used to reach a [Controller]
 #[derive(Debug)]
 pub enum ConnexionType {
+    /// Network connexion to the controller
     Tcp(SocketAddr, TcpStream),
+    /// Serial port connexion to the controller
     Serial(String, Box<dyn SerialPort>),
 }
 
@@ -41,52 +156,303 @@ impl Display for ConnexionType {
     }
 }
 
+/// Receiving cards addressed by a command
+#[derive(PartialEq, Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Target {
+    /// Every receiving card of the screen
+    Screen,
+    /// Every receiving card chained on an output port of the controller
+    Port(u8),
+    /// A single receiving card, by output port and position in the chain
+    Card {
+        /// Output port of the controller
+        port: u8,
+        /// Position of the card on the port
+        card: u16,
+    },
+}
+
+impl Target {
+    /// Returns the port and receiving card addresses of this target
+    pub(crate) fn addresses(self) -> (u8, u16) {
+        match self {
+            Target::Screen => (0xFF, 0xFFFF),
+            Target::Port(port) => (port, 0xFFFF),
+            Target::Card { port, card } => (port, card),
+        }
+    }
+}
+
+/// Explicit acknowledgement required by operations that discard settings
+///
+/// Built with [Confirmation::discard_settings] and consumed by the call it guards
+#[derive(Debug)]
+pub struct Confirmation(());
+
+impl Confirmation {
+    /// Confirms that the settings currently applied to the controller may be lost
+    pub fn discard_settings() -> Self {
+        Confirmation(())
+    }
+}
+
+/// A Novastar sending card reachable through a [ConnexionType]
 #[derive(Debug)]
 pub struct Controller {
     pub(crate) card_type: SenderCardType,
     pub(crate) connexion: ConnexionType,
+    retry: RetryPolicy,
+    span: Span,
+    /// IDs of the screens of an H Series controller, fetched on the first brightness request
+    #[cfg(feature = "h-series")]
+    h_series_screens: Option<Vec<u32>>,
 }
 
 impl Controller {
+    pub(crate) fn new(card_type: SenderCardType, connexion: ConnexionType) -> Self {
+        let span = info_span!("controller", connection = %connexion, model = %card_type);
+        Self {
+            card_type,
+            connexion,
+            retry: RetryPolicy::default(),
+            span,
+            #[cfg(feature = "h-series")]
+            h_series_screens: None,
+        }
+    }
+
     //pub fn update_last_seen(mut self) {
     //  self.last_seen = chrono::offset::Utc::now();
     //}
 
+    /// Returns the model of this controller
     pub fn card_type(&self) -> SenderCardType {
         self.card_type
     }
 
+    /// Returns the raw model ID reported by this controller, also for models this crate does not know
+    pub fn model_id(&self) -> u16 {
+        self.card_type.into()
+    }
+
+    /// Returns a description of this controller, which can be stored and used to reconnect to it
+    pub fn descriptor(&self) -> ControllerDescriptor {
+        let transport = match &self.connexion {
+            ConnexionType::Tcp(addr, _) => Transport::Tcp(*addr),
+            ConnexionType::Serial(port_name, port) => Transport::Serial {
+                port_name: port_name.clone(),
+                baud_rate: port.baud_rate().unwrap_or(115200),
+            },
+        };
+        ControllerDescriptor {
+            transport,
+            model: self.card_type,
+        }
+    }
+
+    /// Returns the hardware capabilities of this controller
+    pub fn capabilities(&self) -> Capabilities {
+        self.card_type.capabilities()
+    }
+
+    /// Returns an [Error::Unsupported] when the model of this controller lacks `feature`
+    fn require(&self, feature: Feature) -> Result<(), Error> {
+        if self.capabilities().supports(feature) {
+            Ok(())
+        } else {
+            Err(Error::Unsupported(feature))
+        }
+    }
+
+    /// Returns the transport used to reach this controller
     pub fn connection(&self) -> &ConnexionType {
         &self.connexion
     }
 
+    /// Returns how this controller recovers from a broken connexion
+    pub fn retry_policy(&self) -> RetryPolicy {
+        self.retry
+    }
+
+    /// Sets how this controller recovers from a broken connexion
+    pub fn set_retry_policy(&mut self, policy: RetryPolicy) {
+        self.retry = policy;
+    }
+
+    /// Reopens the connexion from the stored address or port name, and checks the model ID reported
+    ///
+    /// Called on failed requests according to the [RetryPolicy]
+    pub fn reconnect(&mut self) -> Result<(), Error> {
+        let fresh = match self.descriptor().connect() {
+            Ok(fresh) => fresh,
+            Err(err) => match &mut self.connexion {
+                // A serial port still open cannot be opened twice, the handshake is run on it again
+                ConnexionType::Serial(_, port) => {
+                    debug!(%err, "reusing the open serial port");
+                    port.clear(ClearBuffer::All)
+                        .map_err(|err| Error::Connection(err.into()))?;
+                    let found = self.model_id_query()?;
+                    return self.check_model(found);
+                }
+                ConnexionType::Tcp(..) => return Err(err),
+            },
+        };
+        self.check_model(fresh.model_id())?;
+        self.connexion = fresh.connexion;
+        Ok(())
+    }
+
+    /// Returns an [Error::ModelChanged] when `found` is not the model ID of this controller
+    fn check_model(&self, found: u16) -> Result<(), Error> {
+        if found == self.model_id() {
+            Ok(())
+        } else {
+            Err(Error::ModelChanged {
+                expected: self.model_id(),
+                found,
+            })
+        }
+    }
+
+    /// Returns a client for the web API of an H Series controller reached through the network
+    #[cfg(feature = "h-series")]
+    pub fn h_series(&self) -> Option<HSeriesClient> {
+        match (&self.connexion, self.card_type) {
+            (
+                ConnexionType::Tcp(addr, _),
+                SenderCardType::H_series_H9 | SenderCardType::H_series_H20,
+            ) => Some(HSeriesClient::new(
+                &SocketAddr::new(addr.ip(), 80).to_string(),
+            )),
+            _ => None,
+        }
+    }
+
+    /// Sets the global brightness of every receiving card
+    ///
+    /// H Series controllers have the brightness of each of their screens set through their web API,
+    /// their screens being listed on the first call only
     pub fn set_brightness(&mut self, value: u8) -> Result<(), Error> {
-        let out: Vec<u8> = build_tx_scanboard(
-            OpCode::Write,
-            FeatureAddress::GlobalBrightnessAddr,
-            &[value],
-        );
-        self.write_all(&out).map_err(Error::Write)
+        #[cfg(feature = "h-series")]
+        if let Some(client) = self.h_series() {
+            let percent = ((value as u16 * 100 + 127) / 255) as u8;
+            for screen in self.h_series_screens(&client)? {
+                client.set_brightness(screen, percent)?;
+            }
+            return Ok(());
+        }
+
+        self.write_scanboard(FeatureAddress::GlobalBrightnessAddr, &[value])
     }
 
+    /// Sets the red, green and blue brightness of every receiving card
+    pub fn set_rgb_brightness(&mut self, red: u8, green: u8, blue: u8) -> Result<(), Error> {
+        self.write_scanboard(ScannerAddress::RedBrightnessAddr, &[red, green, blue])
+    }
+
+    /// Returns the global brightness of the controller
+    ///
+    /// H Series controllers report the brightness of their first screen, read through their web API
     pub fn brightness(&mut self) -> Result<u8, Error> {
-        self.write_all(&build_tx_sender(
-            OpCode::Read,
-            0,
-            FeatureAddress::GlobalBrightnessAddr,
-            &[0],
-        ))
-        .map_err(Error::Write)?;
+        #[cfg(feature = "h-series")]
+        if let Some(client) = self.h_series() {
+            let screen = *self
+                .h_series_screens(&client)?
+                .first()
+                .ok_or_else(|| HSeriesError::MissingData("screen/readAllList".to_string()))?;
+            let percent = client.brightness(screen)?.min(100);
+            return Ok(((percent as u16 * 255 + 50) / 100) as u8);
+        }
 
-        self.flush().map_err(Error::Flush)?;
+        Ok(self.read_sender(FeatureAddress::GlobalBrightnessAddr, 1)?[0])
+    }
 
-        let rx_buff: &mut [u8; 21] = &mut [0; 21];
-        self.read_exact(rx_buff).map_err(Error::Read)?;
-        NovastarPacket::decode(rx_buff)
-            .map(|packet| packet.data[0])
-            .map_err(Error::PacketDecoding)
+    /// Returns the IDs of the screens of an H Series controller, only asked to `client` once
+    #[cfg(feature = "h-series")]
+    fn h_series_screens(&mut self, client: &HSeriesClient) -> Result<Vec<u32>, Error> {
+        if let Some(screens) = &self.h_series_screens {
+            return Ok(screens.clone());
+        }
+        let screens: Vec<u32> = client
+            .screens()?
+            .iter()
+            .map(|screen| screen.screen_id)
+            .collect();
+        self.h_series_screens = Some(screens.clone());
+        Ok(screens)
+    }
+
+    /// Fades the global brightness to `target` over `duration`, see [Controller::fade_brightness_cancellable]
+    pub fn fade_brightness(
+        &mut self,
+        target: u8,
+        duration: Duration,
+        curve: FadeCurve,
+    ) -> Result<u8, Error> {
+        self.fade_brightness_cancellable(target, duration, curve, &CancelFade::new())
+    }
+
+    /// Fades the global brightness from its current value to `target` over `duration`
+    ///
+    /// The brightness is written 25 times per second following `curve`, 4 times per second on H
+    /// Series controllers driven through their web API. Returns the brightness reached, short of
+    /// `target` when the fade was stopped through `cancel`
+    pub fn fade_brightness_cancellable(
+        &mut self,
+        target: u8,
+        duration: Duration,
+        curve: FadeCurve,
+        cancel: &CancelFade,
+    ) -> Result<u8, Error> {
+        self.fade_brightness_from(Instant::now(), target, duration, curve, cancel)
     }
 
+    /// Runs a fade whose progress is measured from `start`, shared by controllers fading in lockstep
+    pub(crate) fn fade_brightness_from(
+        &mut self,
+        start: Instant,
+        target: u8,
+        duration: Duration,
+        curve: FadeCurve,
+        cancel: &CancelFade,
+    ) -> Result<u8, Error> {
+        let from = self.brightness()?;
+        let step = self.fade_step();
+        let mut current = from;
+        let mut tick = start;
+        loop {
+            if cancel.is_cancelled() {
+                return Ok(current);
+            }
+            let progress = if duration.is_zero() {
+                1.0
+            } else {
+                start.elapsed().as_secs_f64() / duration.as_secs_f64()
+            };
+            let value = curve.brightness_at(from, target, progress);
+            if value != current {
+                self.set_brightness(value)?;
+                current = value;
+            }
+            if progress >= 1.0 {
+                return Ok(current);
+            }
+            tick += step;
+            thread::sleep(tick.saturating_duration_since(Instant::now()));
+        }
+    }
+
+    /// Returns the interval between two brightness writes of a fade
+    fn fade_step(&self) -> Duration {
+        #[cfg(feature = "h-series")]
+        if self.h_series().is_some() {
+            return HTTP_FADE_STEP;
+        }
+        FADE_STEP
+    }
+
+    /// Returns the raw model ID reported by the controller
     pub fn model_id_query(&mut self) -> Result<u16, Error> {
         self.write_all(&build_tx_sender(
             OpCode::Read,
@@ -104,6 +470,7 @@ impl Controller {
             .map_err(Error::PacketDecoding)
     }
 
+    /// Resets the communication session with the controller
     pub fn session_reset(&mut self) -> Result<(), Error> {
         self.write_all(&build_tx_sender(
             OpCode::Read,
@@ -114,9 +481,669 @@ impl Controller {
         .map_err(Error::Write)
     }
 
+    /// Turns every LED of the screen off while keeping the video pipeline running
+    pub fn blackout(&mut self) -> Result<(), Error> {
+        self.set_display_state(DisplayState::Blackout)
+    }
+
+    /// Holds the current frame on the screen
+    pub fn freeze(&mut self) -> Result<(), Error> {
+        self.set_display_state(DisplayState::Freeze)
+    }
+
+    /// Shows the input video again after a [Controller::blackout] or a [Controller::freeze]
+    pub fn normal(&mut self) -> Result<(), Error> {
+        self.set_display_state(DisplayState::Normal)
+    }
+
+    /// Returns what the screen is currently showing
+    ///
+    /// Only the flags of the sending card can be read back, and none of them tells a frozen
+    /// screen apart. [DisplayState::Freeze] is therefore inferred when neither the normal nor the
+    /// blackout flag is set, which also covers modes set by other tools than this crate
+    pub fn display_state(&mut self) -> Result<DisplayState, Error> {
+        if self.read_sender(FeatureAddress::ShowNormalAddr, 1)?[0] != 0 {
+            return Ok(DisplayState::Normal);
+        }
+        let black_screen = self.read_sender(FeatureAddress::SenderBlackScreenSleepAddr, 4)?;
+        if black_screen[0] != 0 {
+            Ok(DisplayState::Blackout)
+        } else {
+            Ok(DisplayState::Freeze)
+        }
+    }
+
+    /// Shows a built-in [TestPattern] on the receiving cards of `target`
+    pub fn show_test_pattern(&mut self, pattern: TestPattern, target: Target) -> Result<(), Error> {
+        self.write_scanboard_at(target, FeatureAddress::TestPointAddr, &[pattern.into()])
+    }
+
+    /// Removes any [TestPattern] and shows the input video on the whole screen
+    pub fn clear_test_pattern(&mut self) -> Result<(), Error> {
+        self.show_test_pattern(TestPattern::Normal, Target::Screen)
+    }
+
+    /// Stores the current sending and receiving card parameters so they survive a power cycle
+    pub fn save_to_hardware(&mut self) -> Result<(), Error> {
+        // The reference table has a command block at 0x0100_0000 on both cards, `Sender_cmdBase`
+        // and `Scanner_cmdBase`: this command belongs to the sending card one, and the
+        // receiving cards store their own parameters with the write which follows
+        self.write_sender(FeatureAddress::SaveSendCardsParametersAddr, &[1])?;
+        self.write_scanboard(FeatureAddress::ParameterStore2SpiFlashAddr, &[1])
+    }
+
+    /// Reloads the receiving card parameters stored in flash, discarding unsaved changes
+    pub fn reload_from_flash(&mut self, _confirmation: Confirmation) -> Result<(), Error> {
+        self.write_scanboard(FeatureAddress::ParameterReloadFromSpiFlashAddr, &[1])
+    }
+
+    /// Restores the factory parameters of the controller, discarding every setting
+    pub fn factory_reset(&mut self, _confirmation: Confirmation) -> Result<(), Error> {
+        // Listed with the sending card commands of `Sender_cmdBase` in the reference table
+        self.write_sender(FeatureAddress::ReturnFactoryValuesAddr, &[1])
+    }
+
+    /// Returns the serial number of the controller
+    pub fn serial_number(&mut self) -> Result<u64, Error> {
+        let data = self.read_sender(FeatureAddress::ControllerSnHighAddr, 8)?;
+        Ok(u64::from_le_bytes(to_array(data)?))
+    }
+
+    /// Takes a full copy of the sending and receiving card parameters
+    ///
+    /// Blocks until the controller reports the end of the backup
+    pub fn backup(&mut self) -> Result<ConfigBackup, Error> {
+        self.require(Feature::Backup)?;
+        // Left set by a previous backup, the flag would end the wait before this one completes
+        self.write_sender(FeatureAddress::BackUpFinishFlagAddr, &[0])?;
+        self.write_sender(FeatureAddress::SetBackUpOrRestoreAddr, &[0])?;
+        self.wait_for_flag(FeatureAddress::BackUpFinishFlagAddr)?;
+
+        let header = self.read_sender(FeatureAddress::BackUpFileHeaderAddr, 0x100)?;
+        let sender_parameters = self.read_sender(FeatureAddress::BackUpFileAddr, 0x200)?;
+        let led_len = self.read_sender(FeatureAddress::LedParamLenAddr, 4)?;
+        let led_len = u32::from_le_bytes(to_array(led_len)?) as usize;
+        let mut led_parameters = Vec::with_capacity(led_len.min(LED_PARAM_CHUNK));
+        while led_parameters.len() < led_len {
+            let len = (led_len - led_parameters.len()).min(LED_PARAM_CHUNK);
+            let address = u32::from(FeatureAddress::LedParamInfoAddr) + led_parameters.len() as u32;
+            led_parameters.extend(self.read_register(address, len)?);
+        }
+
+        Ok(ConfigBackup {
+            model_id: self.model_id(),
+            serial_number: self.serial_number()?,
+            header,
+            sender_parameters,
+            led_parameters,
+        })
+    }
+
+    /// Applies a [ConfigBackup] taken from a controller of the same model
+    ///
+    /// Blocks until the controller reports the end of the restore
+    pub fn restore(&mut self, backup: &ConfigBackup) -> Result<(), Error> {
+        self.require(Feature::Backup)?;
+        let model_id = self.model_id();
+        if backup.model_id != model_id {
+            return Err(Error::ModelMismatch {
+                backup: backup.model_id,
+                controller: model_id,
+            });
+        }
+
+        self.write_sender(FeatureAddress::BackUpFileHeaderAddr, &backup.header)?;
+        self.write_sender(FeatureAddress::BackUpFileAddr, &backup.sender_parameters)?;
+        self.write_sender(
+            FeatureAddress::LedParamLenAddr,
+            &(backup.led_parameters.len() as u32).to_le_bytes(),
+        )?;
+        for (index, chunk) in backup.led_parameters.chunks(LED_PARAM_CHUNK).enumerate() {
+            let offset = (index * LED_PARAM_CHUNK) as u32;
+            self.write_register(u32::from(FeatureAddress::LedParamInfoAddr) + offset, chunk)?;
+        }
+        self.write_sender(FeatureAddress::RestoreFinishFlagAddr, &[0])?;
+        self.write_sender(FeatureAddress::SetBackUpOrRestoreAddr, &[1])?;
+        self.wait_for_flag(FeatureAddress::RestoreFinishFlagAddr)
+    }
+
+    /// Polls `address` until the controller sets it
+    fn wait_for_flag(&mut self, address: FeatureAddress) -> Result<(), Error> {
+        let start = Instant::now();
+        while self.read_sender(address, 1)?[0] == 0 {
+            if start.elapsed() > BACKUP_TIMEOUT {
+                return Err(Error::Timeout(BACKUP_TIMEOUT));
+            }
+            thread::sleep(POLL_INTERVAL);
+        }
+        Ok(())
+    }
+
+    /// Reads and parses the EDID presented by the inputs of the controller
+    pub fn edid(&mut self) -> Result<Edid, Error> {
+        let bytes = self.read_sender(FeatureAddress::SenderEdidSpaceAddr, EDID_LEN)?;
+        Ok(Edid::parse(&bytes)?)
+    }
+
+    /// Replaces the EDID presented by the inputs of the controller
+    pub fn set_edid(&mut self, edid: &Edid) -> Result<(), Error> {
+        self.write_sender(FeatureAddress::SenderEdidSpaceAddr, &edid.to_bytes())
+    }
+
+    /// Returns the number of output ports of the controller, `None` when it is unknown
+    pub fn output_port_count(&self) -> Option<usize> {
+        self.capabilities().ethernet_ports.map(usize::from)
+    }
+
+    /// Returns the redundancy role of the controller and of each of its output ports
+    pub fn redundancy_state(&mut self) -> Result<RedundancyState, Error> {
+        self.require(Feature::Redundancy)?;
+        let (Some(port_count), Some(family)) = (
+            self.output_port_count(),
+            self.capabilities().port_address_family(),
+        ) else {
+            return Err(Error::Unsupported(Feature::Redundancy));
+        };
+        let roles_address = match family {
+            PortAddressFamily::Legacy => FeatureAddress::MasterOrSlaveAddr,
+            PortAddressFamily::New => FeatureAddress::MasterOrSlaveNewAddr,
+            PortAddressFamily::New16 => FeatureAddress::MasterOrSlaveNew16Addr,
+            PortAddressFamily::New32 => FeatureAddress::MasterOrSlaveNew32Addr,
+        };
+        let ports = self.read_sender(roles_address, port_count)?;
+        let device_state = self.read_sender(FeatureAddress::MasterOrSlaveDeviceStateAddr, 1)?;
+        let backup_info = self.read_sender(FeatureAddress::SenderHardWareBackupInfoAddr, 0x1000)?;
+
+        Ok(RedundancyState {
+            device_role: PortRole::from(device_state[0] & 0x01),
+            ports: ports.into_iter().map(PortRole::from).collect(),
+            failover_active: device_state[0] & 0x02 != 0,
+            backup_pairs: decode_backup_pairs(&backup_info),
+        })
+    }
+
+    /// Configures the hot-backup pairs between output ports of the controller,
+    /// an empty slice disabling port backup
+    ///
+    /// Each port can only appear once in `pairs`. Ports are only checked against the number of
+    /// ports of the controller, and pairs against half of it, when it is known
+    pub fn set_port_backup_pairs(&mut self, pairs: &[PortBackupPair]) -> Result<(), Error> {
+        self.require(Feature::Redundancy)?;
+        let port_count = self.output_port_count();
+        let max_pairs = port_count.map_or(u8::MAX as usize, |count| count / 2);
+        if pairs.len() > max_pairs {
+            return Err(Error::OutOfRange {
+                value: u32::try_from(pairs.len()).unwrap_or(u32::MAX),
+                max: max_pairs as u32,
+            });
+        }
+        let mut paired = Vec::with_capacity(2 * pairs.len());
+        for port in pairs.iter().flat_map(|pair| [pair.primary, pair.backup]) {
+            if port as usize >= port_count.unwrap_or(usize::MAX) || paired.contains(&port) {
+                return Err(Error::InvalidPort(port));
+            }
+            paired.push(port);
+        }
+        self.write_sender(
+            FeatureAddress::SenderHardWareBackupInfoAddr,
+            &encode_backup_pairs(pairs),
+        )
+    }
+
+    /// Returns every video processing setting of the controller
+    pub fn video_processing(&mut self) -> Result<VideoProcessing, Error> {
+        self.require(Feature::VideoProcessing)?;
+        Ok(VideoProcessing {
+            scaling_mode: self.scaling_mode()?,
+            screen_offset: self.screen_offset()?,
+            input_crop: self.input_crop()?,
+            output_window: self.output_window()?,
+            mosaic: self.mosaic()?,
+        })
+    }
+
+    /// Returns how the input video is fitted on the screen
+    pub fn scaling_mode(&mut self) -> Result<ScalingMode, Error> {
+        self.require(Feature::VideoProcessing)?;
+        let data = self.read_sender(FeatureAddress::SenderVideoScalingModeAddr, 1)?;
+        ScalingMode::try_from_primitive(data[0])
+            .map_err(|err| Error::UnexpectedValue(err.number.into()))
+    }
+
+    /// Sets how the input video is fitted on the screen
+    pub fn set_scaling_mode(&mut self, mode: ScalingMode) -> Result<(), Error> {
+        self.require(Feature::VideoProcessing)?;
+        self.write_sender(FeatureAddress::SenderVideoScalingModeAddr, &[mode.into()])
+    }
+
+    /// Returns the position of the output on the screen, as `(x, y)`
+    pub fn screen_offset(&mut self) -> Result<(u16, u16), Error> {
+        self.require(Feature::VideoProcessing)?;
+        let data = self.read_sender(FeatureAddress::SenderVideoScreenOffsetAddr, 4)?;
+        Ok((
+            u16::from_le_bytes([data[0], data[1]]),
+            u16::from_le_bytes([data[2], data[3]]),
+        ))
+    }
+
+    /// Moves the output on the screen
+    pub fn set_screen_offset(&mut self, x: u16, y: u16) -> Result<(), Error> {
+        self.require(Feature::VideoProcessing)?;
+        let [x0, x1] = x.to_le_bytes();
+        let [y0, y1] = y.to_le_bytes();
+        self.write_sender(
+            FeatureAddress::SenderVideoScreenOffsetAddr,
+            &[x0, x1, y0, y1],
+        )
+    }
+
+    /// Returns the part of the input which is displayed
+    pub fn input_crop(&mut self) -> Result<Rect, Error> {
+        self.require(Feature::VideoProcessing)?;
+        let data = self.read_sender(FeatureAddress::SenderVideoInputCutAddr, 8)?;
+        Ok(Rect::decode(&data))
+    }
+
+    /// Sets the part of the input which is displayed
+    pub fn set_input_crop(&mut self, crop: Rect) -> Result<(), Error> {
+        self.require(Feature::VideoProcessing)?;
+        self.write_sender(FeatureAddress::SenderVideoInputCutAddr, &crop.encode())
+    }
+
+    /// Returns the part of the screen the cropped input is scaled into
+    pub fn output_window(&mut self) -> Result<Rect, Error> {
+        self.require(Feature::VideoProcessing)?;
+        let data = self.read_sender(FeatureAddress::SenderVideoWindowRectAddr, 8)?;
+        Ok(Rect::decode(&data))
+    }
+
+    /// Sets the part of the screen the cropped input is scaled into
+    pub fn set_output_window(&mut self, window: Rect) -> Result<(), Error> {
+        self.require(Feature::VideoProcessing)?;
+        self.write_sender(FeatureAddress::SenderVideoWindowRectAddr, &window.encode())
+    }
+
+    /// Returns the mosaic shown over the output
+    pub fn mosaic(&mut self) -> Result<Mosaic, Error> {
+        self.require(Feature::VideoProcessing)?;
+        let data = self.read_sender(FeatureAddress::SenderVideoMosaicAddr, 13)?;
+        Ok(Mosaic::decode(&data))
+    }
+
+    /// Sets the mosaic shown over the output
+    pub fn set_mosaic(&mut self, mosaic: Mosaic) -> Result<(), Error> {
+        self.require(Feature::VideoProcessing)?;
+        self.write_sender(FeatureAddress::SenderVideoMosaicAddr, &mosaic.encode())
+    }
+
+    /// Returns the peak luminance the screen can reach, in nits
+    pub fn screen_peak_luminance(&mut self) -> Result<u16, Error> {
+        let data = self.read_sender(FeatureAddress::ScrenPeakLumAddr, 2)?;
+        Ok(u16::from_le_bytes([data[0], data[1]]))
+    }
+
+    /// Sets the peak luminance the screen can reach, in nits
+    ///
+    /// It bounds the tone mapping peak luminance accepted by [Controller::set_hdr_settings]
+    pub fn set_screen_peak_luminance(&mut self, nits: u16) -> Result<(), Error> {
+        self.write_sender(FeatureAddress::ScrenPeakLumAddr, &nits.to_le_bytes())
+    }
+
+    /// Returns the automatic brightness limiting and energy-saving settings
+    pub fn abl_settings(&mut self) -> Result<AblSettings, Error> {
+        self.require(Feature::Abl)?;
+        let data = self.read_sender(FeatureAddress::SenderABLtionAddr, 16)?;
+        Ok(AblSettings::decode(&data))
+    }
+
+    /// Applies automatic brightness limiting and energy-saving settings
+    pub fn set_abl_settings(&mut self, settings: &AblSettings) -> Result<(), Error> {
+        self.require(Feature::Abl)?;
+        if settings.threshold > 100 || settings.min_level > 100 {
+            return Err(Error::OutOfRange {
+                value: settings.threshold.max(settings.min_level).into(),
+                max: 100,
+            });
+        }
+        let current = self.read_sender(FeatureAddress::SenderABLtionAddr, 16)?;
+        self.write_sender(
+            FeatureAddress::SenderABLtionAddr,
+            &settings.encode(to_array(current)?),
+        )
+    }
+
+    /// Turns automatic brightness limiting on or off
+    pub fn set_abl_enabled(&mut self, enabled: bool) -> Result<(), Error> {
+        self.require(Feature::Abl)?;
+        self.write_sender(FeatureAddress::SenderABLOpreationAddr, &[enabled as u8])
+    }
+
+    /// Turns energy saving on or off
+    pub fn set_energy_saving(&mut self, enabled: bool) -> Result<(), Error> {
+        self.require(Feature::Abl)?;
+        self.write_sender(FeatureAddress::SenderEDEOpreationAddr, &[enabled as u8])
+    }
+
+    /// Returns the HDR10 and HLG settings of the controller
+    pub fn hdr_settings(&mut self) -> Result<HdrSettings, Error> {
+        self.require(Feature::Hdr)?;
+        let info = self.read_sender(FeatureAddress::HDRInfoAddr, 4)?;
+        let hdr10_min_luminance = self.read_sender(FeatureAddress::HDR10MinLum, 1)?[0];
+        let hlg_model = self.read_sender(FeatureAddress::HLGModelAddr, 1)?[0];
+        Ok(HdrSettings::decode(&info, hdr10_min_luminance, hlg_model))
+    }
+
+    /// Applies HDR10 and HLG settings to the controller
+    ///
+    /// The tone mapping peak luminance cannot exceed the peak luminance of the screen
+    pub fn set_hdr_settings(&mut self, settings: &HdrSettings) -> Result<(), Error> {
+        self.require(Feature::Hdr)?;
+        let max = self.screen_peak_luminance()?;
+        if settings.screen_peak_luminance > max {
+            return Err(Error::OutOfRange {
+                value: settings.screen_peak_luminance.into(),
+                max: max.into(),
+            });
+        }
+        self.write_sender(FeatureAddress::HDRInfoAddr, &settings.encode_info())?;
+        self.write_sender(FeatureAddress::HDR10MinLum, &[settings.hdr10_min_luminance])?;
+        self.write_sender(FeatureAddress::HLGModelAddr, &[settings.hlg_model])
+    }
+
+    /// Returns the field rate, genlock and latency settings of the controller
+    pub fn sync_settings(&mut self) -> Result<SyncSettings, Error> {
+        let mode = self.read_sender(FeatureAddress::FieldRateModeAddr, 1)?[0];
+        let field_rate = self.read_sender(FeatureAddress::SetFieldRateAddr, 2)?;
+        let genlock_present = self.read_sender(FeatureAddress::IsHasGenLockAddr, 1)?[0];
+        let low_latency = self.read_sender(FeatureAddress::LowDelayAddr, 1)?[0];
+        Ok(SyncSettings {
+            field_rate_mode: FieldRateMode::try_from_primitive(mode)
+                .map_err(|err| Error::UnexpectedValue(err.number.into()))?,
+            field_rate: SyncSettings::decode_field_rate(&field_rate),
+            genlock_present: genlock_present != 0,
+            low_latency: low_latency != 0,
+        })
+    }
+
+    /// Applies field rate and latency settings to the sending and receiving cards
+    pub fn set_sync_settings(&mut self, settings: &SyncSettings) -> Result<(), Error> {
+        if !(0.0..=MAX_FIELD_RATE).contains(&settings.field_rate) {
+            return Err(Error::InvalidFieldRate(settings.field_rate));
+        }
+        if settings.field_rate_mode == FieldRateMode::Genlock {
+            self.require(Feature::Genlock)?;
+        }
+        self.write_sender(
+            FeatureAddress::FieldRateModeAddr,
+            &[settings.field_rate_mode.into()],
+        )?;
+        self.write_sender(
+            FeatureAddress::SetFieldRateAddr,
+            &settings.encode_field_rate(),
+        )?;
+        self.write_sender(FeatureAddress::LowDelayAddr, &[settings.low_latency as u8])?;
+        self.write_scanboard(
+            FeatureAddress::ScannerLowDelayAddr,
+            &[settings.low_latency as u8],
+        )
+    }
+
+    /// Returns `true` when the output is locked to a present genlock signal
+    ///
+    /// Returns `false` when genlock is not selected or when the genlock signal is lost
+    pub fn is_genlocked(&mut self) -> Result<bool, Error> {
+        self.require(Feature::Genlock)?;
+        Ok(self.sync_settings()?.is_genlocked())
+    }
+
+    /// Returns the mirroring of the output image
+    pub fn mirror_mode(&mut self) -> Result<MirrorMode, Error> {
+        let data = self.read_sender(FeatureAddress::MirrorModeFirAddr, 1)?;
+        MirrorMode::try_from_primitive(data[0])
+            .map_err(|err| Error::UnexpectedValue(err.number.into()))
+    }
+
+    /// Mirrors the output image, on both output groups of the controller
+    pub fn set_mirror_mode(&mut self, mode: MirrorMode) -> Result<(), Error> {
+        self.write_sender(FeatureAddress::MirrorModeFirAddr, &[mode.into()])?;
+        self.write_sender(FeatureAddress::MirrorModeSecAddr, &[mode.into()])
+    }
+
+    /// Returns the rotation of the image shown by each cabinet
+    pub fn cabinet_rotation(&mut self) -> Result<CabinetRotation, Error> {
+        let data = self.read_sender(FeatureAddress::CabinetRotationAddr, 1)?;
+        CabinetRotation::try_from_primitive(data[0])
+            .map_err(|err| Error::UnexpectedValue(err.number.into()))
+    }
+
+    /// Rotates the image shown by each cabinet, for cabinets hung rotated or upside down
+    pub fn set_cabinet_rotation(&mut self, rotation: CabinetRotation) -> Result<(), Error> {
+        self.write_sender(FeatureAddress::CabinetRotationAddr, &[rotation.into()])
+    }
+
+    /// Returns the stereoscopic 3D settings
+    pub fn three_d_settings(&mut self) -> Result<ThreeDSettings, Error> {
+        self.require(Feature::ThreeD)?;
+        let enabled = self.read_sender(FeatureAddress::Enable3DAddr, 1)?[0];
+        let delay = self.read_sender(FeatureAddress::ThreeDPerAddr, 2)?;
+        Ok(ThreeDSettings {
+            enabled: enabled != 0,
+            right_eye_delay: u16::from_le_bytes([delay[0], delay[1]]),
+        })
+    }
+
+    /// Applies stereoscopic 3D settings
+    pub fn set_three_d_settings(&mut self, settings: &ThreeDSettings) -> Result<(), Error> {
+        self.require(Feature::ThreeD)?;
+        self.write_sender(
+            FeatureAddress::ThreeDPerAddr,
+            &settings.right_eye_delay.to_le_bytes(),
+        )?;
+        self.write_sender(FeatureAddress::Enable3DAddr, &[settings.enabled as u8])
+    }
+
+    /// Returns the date and time of the real-time clock driving the brightness schedule
+    pub fn clock(&mut self) -> Result<NaiveDateTime, Error> {
+        let data = self.read_sender(FeatureAddress::RtcoAddr, 6)?;
+        crate::clock::decode(&data).ok_or(Error::InvalidClock(data))
+    }
+
+    /// Sets the real-time clock of the controller
+    ///
+    /// The clock counts years from 2000 on one byte, other years return an [Error::InvalidClockYear]
+    pub fn set_clock(&mut self, time: NaiveDateTime) -> Result<(), Error> {
+        let data = crate::clock::encode(time).ok_or(Error::InvalidClockYear(time.year()))?;
+        self.write_sender(FeatureAddress::RtcoAddr, &data)
+    }
+
+    /// Sets the real-time clock of the controller to the local time of this host
+    pub fn sync_clock(&mut self) -> Result<(), Error> {
+        self.set_clock(crate::clock::now())
+    }
+
+    /// Returns how far the clock of the controller is ahead of the local time of this host
+    pub fn clock_drift(&mut self) -> Result<TimeDelta, Error> {
+        Ok(self.clock()? - crate::clock::now())
+    }
+
+    fn set_display_state(&mut self, state: DisplayState) -> Result<(), Error> {
+        self.write_scanboard(ScannerAddress::DisplayModeAddr, &[state.into()])?;
+        self.write_sender(
+            FeatureAddress::SenderBlackScreenSleepAddr,
+            &[(state == DisplayState::Blackout) as u8, 0, 0, 0],
+        )?;
+        self.write_sender(
+            FeatureAddress::ShowNormalAddr,
+            &[(state == DisplayState::Normal) as u8],
+        )
+    }
+
+    /// Writes `data` at `address` on the sending card
+    pub(crate) fn write_sender(
+        &mut self,
+        address: FeatureAddress,
+        data: &[u8],
+    ) -> Result<(), Error> {
+        self.write_register(address.into(), data)
+    }
+
+    /// Reads `len` bytes at `address` on the sending card
+    pub(crate) fn read_sender(
+        &mut self,
+        address: FeatureAddress,
+        len: usize,
+    ) -> Result<Vec<u8>, Error> {
+        self.read_register(address.into(), len)
+    }
+
+    /// Writes raw `data` at the register `address` of the sending card, and waits for the acknowledgement
+    ///
+    /// Meant for diagnostics, prefer the dedicated methods which validate their values
+    pub fn write_register(&mut self, address: u32, data: &[u8]) -> Result<(), Error> {
+        if data.len() > u16::MAX as usize {
+            return Err(Error::PayloadTooLarge(data.len()));
+        }
+        self.traced(
+            DeviceType::Controller,
+            OpCode::Write,
+            address,
+            |controller| {
+                controller
+                    .write_all(&build_tx_sender(OpCode::Write, 0, address, data))
+                    .map_err(Error::Write)?;
+                controller.flush().map_err(Error::Flush)?;
+
+                let rx_buff: &mut [u8; 20] = &mut [0; 20];
+                controller.read_exact(rx_buff).map_err(Error::Read)?;
+                NovastarPacket::decode(rx_buff)
+                    .map(|_| ())
+                    .map_err(Error::PacketDecoding)
+            },
+        )
+    }
+
+    /// Reads `len` raw bytes at the register `address` of the sending card
+    pub fn read_register(&mut self, address: u32, len: usize) -> Result<Vec<u8>, Error> {
+        if len > u16::MAX as usize {
+            return Err(Error::PayloadTooLarge(len));
+        }
+        self.traced(
+            DeviceType::Controller,
+            OpCode::Read,
+            address,
+            |controller| {
+                controller
+                    .write_all(&build_tx_sender(OpCode::Read, 0, address, &vec![0; len]))
+                    .map_err(Error::Write)?;
+                controller.flush().map_err(Error::Flush)?;
+
+                let mut rx_buff = vec![0; len + 20];
+                controller.read_exact(&mut rx_buff).map_err(Error::Read)?;
+                let packet = NovastarPacket::decode(&rx_buff).map_err(Error::PacketDecoding)?;
+                if packet.data.len() < len {
+                    return Err(Error::ShortReply {
+                        expected: len,
+                        received: packet.data.len(),
+                    });
+                }
+                Ok(packet.data.to_vec())
+            },
+        )
+    }
+
+    /// Writes `data` at `address` on every receiving card
+    pub(crate) fn write_scanboard(
+        &mut self,
+        address: impl Into<u32>,
+        data: &[u8],
+    ) -> Result<(), Error> {
+        self.write_scanboard_at(Target::Screen, address, data)
+    }
+
+    /// Writes `data` at `address` on the receiving cards of `target`
+    pub(crate) fn write_scanboard_at(
+        &mut self,
+        target: Target,
+        address: impl Into<u32>,
+        data: &[u8],
+    ) -> Result<(), Error> {
+        let (port_addr, scanboard_addr) = target.addresses();
+        let address = address.into();
+        self.traced(
+            DeviceType::Scanboard,
+            OpCode::Write,
+            address,
+            |controller| {
+                controller
+                    .write_all(&build_tx_scanboard_at(
+                        OpCode::Write,
+                        port_addr,
+                        scanboard_addr,
+                        address,
+                        data,
+                    ))
+                    .map_err(Error::Write)?;
+                controller.flush().map_err(Error::Flush)
+            },
+        )
+    }
+
+    /// Runs `request` in a span carrying the register address, the packet serial and the latency
+    ///
+    /// A broken connexion is reopened, and the request sent again, as set by the [RetryPolicy].
+    /// Nothing is sent to controllers which are not driven through the binary protocol
+    fn traced<T>(
+        &mut self,
+        device: DeviceType,
+        op_code: OpCode,
+        address: u32,
+        mut request: impl FnMut(&mut Self) -> Result<T, Error>,
+    ) -> Result<T, Error> {
+        self.require(Feature::RegisterAccess)?;
+        let register = register_name(device, address).unwrap_or_else(|| format!("{address:#010x}"));
+        let span = debug_span!(
+            parent: &self.span,
+            "request",
+            op = ?op_code,
+            address = %register,
+            serial = field::Empty,
+            latency_us = field::Empty,
+        );
+        let _entered = span.enter();
+        let start = Instant::now();
+        let resend = match op_code {
+            OpCode::Read => self.retry.resend_reads,
+            OpCode::Write => self.retry.resend_writes,
+        };
+        let mut result = request(self);
+        let mut attempt = 0;
+        while let Err(err) = &result {
+            if !err.is_connection_error() || attempt >= self.retry.attempts {
+                break;
+            }
+            attempt += 1;
+            debug!(%err, attempt, "reconnecting");
+            thread::sleep(self.retry.delay(attempt));
+            if let Err(err) = self.reconnect() {
+                debug!(%err, attempt, "reconnection failed");
+                continue;
+            }
+            if !resend {
+                break;
+            }
+            result = request(self);
+        }
+        span.record("latency_us", start.elapsed().as_micros() as u64);
+        if let Err(err) = &result {
+            debug!(%err, "request failed");
+        }
+        result
+    }
+
     /// Tries to connect to a Novastar [Controller] on the provided [SocketAddr]
     pub fn try_from_tcp_addr(addr: SocketAddr) -> Result<Self, Error> {
-        let mut stream = TcpStream::connect(addr).map_err(Error::Connection)?;
+        let mut stream =
+            TcpStream::connect_timeout(&addr, CONNECT_TIMEOUT).map_err(Error::Connection)?;
         stream.set_read_timeout(Some(Duration::from_secs(1))).map_err(Error::Connection)?;
         let tx_buff = build_tx_sender(
             OpCode::Read,
@@ -133,20 +1160,11 @@ impl Controller {
         let packet = NovastarPacket::decode(rx_buff)?;
 
         let dev_id: u16 = u16::from_le_bytes([packet.data[0], packet.data[1]]);
-        
-        let dev_model =
-            SenderCardType::try_from_primitive(dev_id).unwrap_or(SenderCardType::Unknown);
-            
-        #[cfg(feature = "debug")]
-        if dev_model == SenderCardType::Unknown {
-            println!("Controller returned model ID {dev_id}");
-            println!("{:01$x} ", dev_id, 2);
-        }
-
-        Ok(Self {
-            card_type: dev_model,
-            connexion: crate::ConnexionType::Tcp(addr, stream),
-        })
+
+        Ok(Self::new(
+            SenderCardType::from_primitive(dev_id),
+            crate::ConnexionType::Tcp(addr, stream),
+        ))
     }
 }
 
@@ -173,3 +1191,12 @@ impl Write for Controller {
         }
     }
 }
+
+/// Converts the data of a read into an array, failing when the controller sent too few bytes
+fn to_array<const N: usize>(data: Vec<u8>) -> Result<[u8; N], Error> {
+    let received = data.len();
+    data.try_into().map_err(|_| Error::ShortReply {
+        expected: N,
+        received,
+    })
+}
@@ -0,0 +1,62 @@
+use std::io::{self, ErrorKind};
+use std::net::SocketAddr;
+
+use crate::controller::{Controller, Error};
+use crate::serial::try_com_connect;
+use crate::types::SenderCardType;
+
+/// Address used to reach a [Controller], without the open connexion
+#[derive(PartialEq, Eq, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Transport {
+    /// Network connexion to the controller
+    Tcp(SocketAddr),
+    /// Serial port connexion to the controller
+    Serial {
+        /// Name of the serial port
+        port_name: String,
+        /// Speed of the serial port
+        baud_rate: u32,
+    },
+}
+
+/// Description of a [Controller] which can be stored and connected to again later
+///
+/// Obtained with [Controller::descriptor]
+#[derive(PartialEq, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ControllerDescriptor {
+    /// Address of the controller
+    pub transport: Transport,
+    /// Model of the controller when it was described
+    pub model: SenderCardType,
+}
+
+impl ControllerDescriptor {
+    /// Opens a new connexion to the described controller, and checks the model ID it reports
+    pub fn connect(&self) -> Result<Controller, Error> {
+        let controller = match &self.transport {
+            Transport::Tcp(addr) => Controller::try_from_tcp_addr(*addr),
+            Transport::Serial {
+                port_name,
+                baud_rate,
+            } => try_com_connect(port_name, *baud_rate)
+                .map_err(Error::Connection)?
+                .ok_or_else(|| {
+                    Error::Connection(io::Error::new(
+                        ErrorKind::NotFound,
+                        format!("no controller answered on {port_name}"),
+                    ))
+                }),
+        }?;
+        let expected = self.model.into();
+        if controller.model_id() == expected {
+            Ok(controller)
+        } else {
+            Err(Error::ModelChanged {
+                expected,
+                found: controller.model_id(),
+            })
+        }
+    }
+}
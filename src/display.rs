@@ -0,0 +1,41 @@
+use num_enum::{IntoPrimitive, TryFromPrimitive};
+
+/// Mirroring of the output image
+#[derive(PartialEq, Eq, Clone, Copy, Debug, TryFromPrimitive, IntoPrimitive)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[repr(u8)]
+pub enum MirrorMode {
+    /// The image is shown as is
+    Off = 0x00,
+    /// The image is flipped left to right
+    Horizontal = 0x01,
+    /// The image is flipped top to bottom
+    Vertical = 0x02,
+    /// The image is flipped both ways
+    Both = 0x03,
+}
+
+/// Clockwise rotation of the image shown by each cabinet
+#[derive(PartialEq, Eq, Clone, Copy, Debug, TryFromPrimitive, IntoPrimitive)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[repr(u8)]
+pub enum CabinetRotation {
+    /// Cabinets are mounted upright
+    None = 0x00,
+    /// Cabinets are rotated by 90°
+    Rotate90 = 0x01,
+    /// Cabinets are mounted upside down
+    Rotate180 = 0x02,
+    /// Cabinets are rotated by 270°
+    Rotate270 = 0x03,
+}
+
+/// Stereoscopic 3D settings of a [crate::Controller]
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ThreeDSettings {
+    /// Frames are shown alternately to the left and the right eye
+    pub enabled: bool,
+    /// Delay of the right eye frame relative to the left eye frame
+    pub right_eye_delay: u16,
+}
@@ -0,0 +1,386 @@
+//! This module lets a lighting desk drive a [Controller] like any other fixture, through DMX
+//! universes received over Art-Net or sACN (E1.31)
+
+use std::io::{self, ErrorKind};
+use std::net::{Ipv4Addr, SocketAddr, UdpSocket};
+use std::time::{Duration, Instant};
+
+use thiserror::Error;
+use tracing::{trace, warn};
+
+use crate::controller::{Controller, Error, Target};
+use crate::types::TestPattern;
+
+/// UDP port of Art-Net
+pub const ARTNET_PORT: u16 = 6454;
+/// UDP port of sACN
+pub const SACN_PORT: u16 = 5568;
+
+/// Shortest interval between two updates of the controller by default, 25 per second
+const DEFAULT_RATE_LIMIT: Duration = Duration::from_millis(40);
+/// Largest datagram read, above the 638 bytes of a full sACN packet
+const MAX_DATAGRAM_LEN: usize = 1024;
+
+const ARTNET_ID: &[u8; 8] = b"Art-Net\0";
+const ARTNET_OP_DMX: u16 = 0x5000;
+const ARTNET_DATA_OFFSET: usize = 18;
+
+const ACN_ID: &[u8; 12] = b"ASC-E1.17\0\0\0";
+const SACN_ROOT_VECTOR: u32 = 0x0000_0004;
+const SACN_FRAMING_VECTOR: u32 = 0x0000_0002;
+const SACN_DMP_VECTOR: u8 = 0x02;
+const SACN_PREVIEW_DATA: u8 = 0x80;
+const SACN_STREAM_TERMINATED: u8 = 0x40;
+const SACN_DATA_OFFSET: usize = 126;
+
+/// Test patterns selected by a DMX channel, 10 values each from 10, below is the input video
+const TEST_PATTERNS: [TestPattern; 10] = [
+    TestPattern::Red,
+    TestPattern::Green,
+    TestPattern::Blue,
+    TestPattern::White,
+    TestPattern::HorizontalLines,
+    TestPattern::VerticalLines,
+    TestPattern::DiagonalLines,
+    TestPattern::Gradient,
+    TestPattern::Grid,
+    TestPattern::Aging,
+];
+
+/// Errors returned while bridging DMX to a [Controller]
+#[derive(Error, Debug)]
+pub enum DmxError {
+    /// Receiving DMX failed
+    #[error("DMX socket error: {0}")]
+    Io(#[from] io::Error),
+    /// Updating the controller failed
+    #[error("controller error: {0}")]
+    Controller(#[from] Error),
+}
+
+/// Network protocol carrying the DMX universes
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum DmxProtocol {
+    /// Art-Net, on [ARTNET_PORT]
+    ArtNet,
+    /// sACN (E1.31), on [SACN_PORT]
+    Sacn,
+}
+
+impl DmxProtocol {
+    /// Returns the UDP port of the protocol
+    pub fn port(self) -> u16 {
+        match self {
+            DmxProtocol::ArtNet => ARTNET_PORT,
+            DmxProtocol::Sacn => SACN_PORT,
+        }
+    }
+
+    /// Returns the DMX universe carried by `datagram`, or [None] for any other packet
+    pub fn parse(self, datagram: &[u8]) -> Option<DmxFrame> {
+        match self {
+            DmxProtocol::ArtNet => parse_artnet(datagram),
+            DmxProtocol::Sacn => parse_sacn(datagram),
+        }
+    }
+}
+
+/// Values of a DMX universe
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct DmxFrame {
+    /// Art-Net port address or sACN universe number
+    pub universe: u16,
+    /// Channel values, starting with channel 1
+    pub channels: Vec<u8>,
+}
+
+impl DmxFrame {
+    /// Returns the value of `channel`, counted from 1, or [None] when the frame is shorter
+    pub fn channel(&self, channel: u16) -> Option<u8> {
+        self.channels
+            .get((channel as usize).checked_sub(1)?)
+            .copied()
+    }
+}
+
+/// Reads an ArtDmx packet
+pub fn parse_artnet(datagram: &[u8]) -> Option<DmxFrame> {
+    if datagram.len() < ARTNET_DATA_OFFSET
+        || &datagram[0..8] != ARTNET_ID
+        || u16::from_le_bytes([datagram[8], datagram[9]]) != ARTNET_OP_DMX
+    {
+        return None;
+    }
+    let universe = u16::from_le_bytes([datagram[14], datagram[15]]) & 0x7FFF;
+    let len = u16::from_be_bytes([datagram[16], datagram[17]]) as usize;
+    let channels = &datagram[ARTNET_DATA_OFFSET..];
+    Some(DmxFrame {
+        universe,
+        channels: channels[..len.min(channels.len())].to_vec(),
+    })
+}
+
+/// Reads an sACN data packet, ignoring preview data and terminated streams
+pub fn parse_sacn(datagram: &[u8]) -> Option<DmxFrame> {
+    let u32_at = |offset: usize| {
+        u32::from_be_bytes([
+            datagram[offset],
+            datagram[offset + 1],
+            datagram[offset + 2],
+            datagram[offset + 3],
+        ])
+    };
+    if datagram.len() < SACN_DATA_OFFSET
+        || &datagram[4..16] != ACN_ID
+        || u32_at(18) != SACN_ROOT_VECTOR
+        || u32_at(40) != SACN_FRAMING_VECTOR
+        || datagram[117] != SACN_DMP_VECTOR
+        || datagram[112] & (SACN_PREVIEW_DATA | SACN_STREAM_TERMINATED) != 0
+        || datagram[125] != 0
+    {
+        return None;
+    }
+    let universe = u16::from_be_bytes([datagram[113], datagram[114]]);
+    // The property count includes the start code
+    let len = (u16::from_be_bytes([datagram[123], datagram[124]]) as usize).saturating_sub(1);
+    let channels = &datagram[SACN_DATA_OFFSET..];
+    Some(DmxFrame {
+        universe,
+        channels: channels[..len.min(channels.len())].to_vec(),
+    })
+}
+
+/// DMX channels driving a [Controller], counted from 1
+///
+/// Channels left to [None] do not change the controller
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DmxMapping {
+    /// Protocol the desk sends
+    pub protocol: DmxProtocol,
+    /// Art-Net port address or sACN universe number
+    pub universe: u16,
+    /// Channel setting the global brightness
+    pub brightness: Option<u16>,
+    /// First of three channels setting the red, green and blue brightness
+    pub rgb: Option<u16>,
+    /// Channel blacking the screen out from 128
+    pub blackout: Option<u16>,
+    /// Channel selecting a [TestPattern], 10 values per pattern from 10 in declaration order,
+    /// anything else shows the input video
+    pub test_pattern: Option<u16>,
+}
+
+impl DmxMapping {
+    /// Creates a mapping of `universe` without any channel
+    pub fn new(protocol: DmxProtocol, universe: u16) -> Self {
+        Self {
+            protocol,
+            universe,
+            brightness: None,
+            rgb: None,
+            blackout: None,
+            test_pattern: None,
+        }
+    }
+
+    fn levels(&self, frame: &DmxFrame) -> Levels {
+        let channel = |channel: Option<u16>| channel.and_then(|channel| frame.channel(channel));
+        Levels {
+            brightness: channel(self.brightness),
+            rgb: self.rgb.and_then(|first| {
+                Some([
+                    frame.channel(first)?,
+                    frame.channel(first.checked_add(1)?)?,
+                    frame.channel(first.checked_add(2)?)?,
+                ])
+            }),
+            blackout: channel(self.blackout).map(|value| value >= 128),
+            test_pattern: channel(self.test_pattern).map(|value| match value / 10 {
+                0 => TestPattern::Normal,
+                slot => TEST_PATTERNS
+                    .get(slot as usize - 1)
+                    .copied()
+                    .unwrap_or(TestPattern::Normal),
+            }),
+        }
+    }
+}
+
+/// Settings of the controller decoded from a DMX frame, [None] where no channel is mapped
+#[derive(PartialEq, Clone, Copy, Debug, Default)]
+struct Levels {
+    brightness: Option<u8>,
+    rgb: Option<[u8; 3]>,
+    blackout: Option<bool>,
+    test_pattern: Option<TestPattern>,
+}
+
+impl Levels {
+    /// Returns the settings of `self` which differ from `applied`
+    fn changes_from(&self, applied: &Levels) -> Levels {
+        fn changed<T: PartialEq + Copy>(new: Option<T>, applied: Option<T>) -> Option<T> {
+            new.filter(|new| Some(*new) != applied)
+        }
+        Levels {
+            brightness: changed(self.brightness, applied.brightness),
+            rgb: changed(self.rgb, applied.rgb),
+            blackout: changed(self.blackout, applied.blackout),
+            test_pattern: changed(self.test_pattern, applied.test_pattern),
+        }
+    }
+}
+
+/// Receives DMX from a lighting desk and applies the mapped channels to a [Controller]
+///
+/// The controller is updated at most once per rate limit, with the latest values received
+#[derive(Debug)]
+pub struct DmxBridge {
+    socket: UdpSocket,
+    mapping: DmxMapping,
+    rate_limit: Duration,
+    applied: Levels,
+    pending: Option<Levels>,
+    last_output: Option<Instant>,
+}
+
+impl DmxBridge {
+    /// Listens on the port of the protocol of `mapping`, joining the multicast group of its
+    /// universe for sACN
+    pub fn bind(mapping: DmxMapping) -> Result<Self, DmxError> {
+        let addr = SocketAddr::from((Ipv4Addr::UNSPECIFIED, mapping.protocol.port()));
+        let bridge = Self::bind_to(mapping, addr)?;
+        if mapping.protocol == DmxProtocol::Sacn {
+            let [high, low] = mapping.universe.to_be_bytes();
+            bridge
+                .socket
+                .join_multicast_v4(&Ipv4Addr::new(239, 255, high, low), &Ipv4Addr::UNSPECIFIED)?;
+        }
+        Ok(bridge)
+    }
+
+    /// Listens on `addr`, receiving unicast packets only
+    pub fn bind_to(mapping: DmxMapping, addr: SocketAddr) -> Result<Self, DmxError> {
+        let socket = UdpSocket::bind(addr)?;
+        socket.set_read_timeout(Some(DEFAULT_RATE_LIMIT))?;
+        Ok(Self {
+            socket,
+            mapping,
+            rate_limit: DEFAULT_RATE_LIMIT,
+            applied: Levels::default(),
+            pending: None,
+            last_output: None,
+        })
+    }
+
+    /// Returns the address the bridge listens on
+    pub fn local_addr(&self) -> Result<SocketAddr, DmxError> {
+        Ok(self.socket.local_addr()?)
+    }
+
+    /// Returns the channels applied to the controller
+    pub fn mapping(&self) -> &DmxMapping {
+        &self.mapping
+    }
+
+    /// Returns the shortest interval between two updates of the controller
+    pub fn rate_limit(&self) -> Duration {
+        self.rate_limit
+    }
+
+    /// Sets the shortest interval between two updates of the controller
+    pub fn set_rate_limit(&mut self, rate_limit: Duration) -> Result<(), DmxError> {
+        // A zero read timeout is refused by the socket
+        self.socket
+            .set_read_timeout(Some(rate_limit.max(Duration::from_millis(1))))?;
+        self.rate_limit = rate_limit;
+        Ok(())
+    }
+
+    /// Waits up to the rate limit for a DMX frame, and updates `controller` when due
+    ///
+    /// Returns whether the controller was updated. Frames received faster than the rate limit
+    /// are merged, only their latest values are sent
+    pub fn poll(&mut self, controller: &mut Controller) -> Result<bool, DmxError> {
+        let mut datagram = [0; MAX_DATAGRAM_LEN];
+        match self.socket.recv(&mut datagram) {
+            Ok(len) => {
+                if let Some(frame) = self.mapping.protocol.parse(&datagram[..len])
+                    && frame.universe == self.mapping.universe
+                {
+                    trace!(
+                        universe = frame.universe,
+                        channels = frame.channels.len(),
+                        "DMX frame"
+                    );
+                    self.pending = Some(self.mapping.levels(&frame));
+                }
+            }
+            Err(err) if matches!(err.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => {}
+            Err(err) => return Err(err.into()),
+        }
+        self.flush(controller)
+    }
+
+    /// Forwards DMX to `controller` until receiving fails
+    ///
+    /// Failed updates of the controller are logged, and tried again with the next frame
+    pub fn run(&mut self, controller: &mut Controller) -> Result<(), DmxError> {
+        loop {
+            match self.poll(controller) {
+                Err(DmxError::Controller(err)) => {
+                    warn!(%err, "updating the controller from DMX failed")
+                }
+                Err(err) => return Err(err),
+                Ok(_) => {}
+            }
+        }
+    }
+
+    /// Sends the pending settings which changed, unless the controller was updated too recently
+    fn flush(&mut self, controller: &mut Controller) -> Result<bool, DmxError> {
+        let Some(levels) = self.pending else {
+            return Ok(false);
+        };
+        let changes = levels.changes_from(&self.applied);
+        if changes == Levels::default() {
+            self.pending = None;
+            return Ok(false);
+        }
+        if self
+            .last_output
+            .is_some_and(|last| last.elapsed() < self.rate_limit)
+        {
+            return Ok(false);
+        }
+
+        self.last_output = Some(Instant::now());
+        if let Some(blackout) = changes.blackout {
+            if blackout {
+                controller.blackout()?;
+            } else {
+                controller.normal()?;
+            }
+            self.applied.blackout = Some(blackout);
+        }
+        if let Some(pattern) = changes.test_pattern {
+            if pattern == TestPattern::Normal {
+                controller.clear_test_pattern()?;
+            } else {
+                controller.show_test_pattern(pattern, Target::Screen)?;
+            }
+            self.applied.test_pattern = Some(pattern);
+        }
+        if let Some(brightness) = changes.brightness {
+            controller.set_brightness(brightness)?;
+            self.applied.brightness = Some(brightness);
+        }
+        if let Some([red, green, blue]) = changes.rgb {
+            controller.set_rgb_brightness(red, green, blue)?;
+            self.applied.rgb = Some([red, green, blue]);
+        }
+        self.pending = None;
+        Ok(true)
+    }
+}
@@ -0,0 +1,537 @@
+use thiserror::Error;
+
+/// Size of the EDID space of a sending card: the base block and one extension block
+pub const EDID_LEN: usize = 256;
+
+const BLOCK_LEN: usize = 128;
+const HEADER: [u8; 8] = [0x00, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0x00];
+const CEA_TAG: u8 = 0x02;
+const CEA_VIDEO_DATA_BLOCK: u8 = 0x02;
+const MONITOR_NAME_TAG: u8 = 0xFC;
+
+/// Modes flagged in the established timings bitmap, from the most significant bit of byte 35
+const ESTABLISHED_TIMINGS: [VideoMode; 17] = [
+    VideoMode::new(720, 400, 70),
+    VideoMode::new(720, 400, 88),
+    VideoMode::new(640, 480, 60),
+    VideoMode::new(640, 480, 67),
+    VideoMode::new(640, 480, 72),
+    VideoMode::new(640, 480, 75),
+    VideoMode::new(800, 600, 56),
+    VideoMode::new(800, 600, 60),
+    VideoMode::new(800, 600, 72),
+    VideoMode::new(800, 600, 75),
+    VideoMode::new(832, 624, 75),
+    VideoMode::new(1024, 768, 87),
+    VideoMode::new(1024, 768, 60),
+    VideoMode::new(1024, 768, 70),
+    VideoMode::new(1024, 768, 75),
+    VideoMode::new(1280, 1024, 75),
+    VideoMode::new(1152, 870, 75),
+];
+
+/// Errors returned while parsing or building an [Edid]
+#[derive(Error, Debug, PartialEq)]
+pub enum EdidError {
+    /// The EDID is shorter than a base block
+    #[error("EDID of {0} bytes is too short")]
+    Length(usize),
+    /// The base block does not start with the EDID header
+    #[error("invalid EDID header")]
+    Header,
+    /// The checksum of a block is wrong
+    #[error("invalid checksum for EDID block {0}")]
+    Checksum(usize),
+    /// The requested mode cannot be described by a detailed timing descriptor
+    #[error("{0} cannot be described in an EDID")]
+    UnsupportedMode(VideoMode),
+}
+
+/// Resolution and refresh rate of a video mode
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct VideoMode {
+    /// Horizontal resolution in pixels
+    pub width: u16,
+    /// Vertical resolution in lines
+    pub height: u16,
+    /// Refresh rate in Hz
+    pub refresh_rate: u8,
+}
+
+impl VideoMode {
+    /// Creates a [VideoMode]
+    pub const fn new(width: u16, height: u16, refresh_rate: u8) -> Self {
+        Self {
+            width,
+            height,
+            refresh_rate,
+        }
+    }
+}
+
+impl std::fmt::Display for VideoMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}x{}@{}Hz", self.width, self.height, self.refresh_rate)
+    }
+}
+
+/// Exact timing of a video mode, as stored in a detailed timing descriptor
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DetailedTiming {
+    /// Pixel clock in kHz, stored with a 10 kHz resolution
+    pub pixel_clock_khz: u32,
+    /// Active pixels per line
+    pub h_active: u16,
+    /// Blanking pixels per line
+    pub h_blanking: u16,
+    /// Pixels between the end of the active area and the horizontal sync
+    pub h_sync_offset: u16,
+    /// Width of the horizontal sync in pixels
+    pub h_sync_width: u16,
+    /// Active lines per frame
+    pub v_active: u16,
+    /// Blanking lines per frame
+    pub v_blanking: u16,
+    /// Lines between the end of the active area and the vertical sync
+    pub v_sync_offset: u8,
+    /// Width of the vertical sync in lines
+    pub v_sync_width: u8,
+    /// The mode is interlaced
+    pub interlaced: bool,
+    /// The horizontal sync is active high
+    pub h_sync_positive: bool,
+    /// The vertical sync is active high
+    pub v_sync_positive: bool,
+}
+
+impl DetailedTiming {
+    /// Builds a CVT reduced blanking timing for `mode`
+    pub fn reduced_blanking(mode: VideoMode) -> Result<Self, EdidError> {
+        const H_BLANKING: u32 = 160;
+        const MIN_V_BLANKING_US: f64 = 460.0;
+        const MIN_V_BLANKING_LINES: u32 = 14;
+
+        if mode.width == 0 || mode.height == 0 || mode.refresh_rate == 0 {
+            return Err(EdidError::UnsupportedMode(mode));
+        }
+
+        let frame_us = 1_000_000.0 / mode.refresh_rate as f64;
+        let line_us = (frame_us - MIN_V_BLANKING_US) / mode.height as f64;
+        if line_us <= 0.0 {
+            return Err(EdidError::UnsupportedMode(mode));
+        }
+        let v_blanking = ((MIN_V_BLANKING_US / line_us).ceil() as u32).max(MIN_V_BLANKING_LINES);
+
+        let h_total = mode.width as u32 + H_BLANKING;
+        let v_total = mode.height as u32 + v_blanking;
+        let pixel_clock_khz =
+            (h_total as u64 * v_total as u64 * mode.refresh_rate as u64 / 10_000 * 10) as u32;
+
+        let timing = Self {
+            pixel_clock_khz,
+            h_active: mode.width,
+            h_blanking: H_BLANKING as u16,
+            h_sync_offset: 48,
+            h_sync_width: 32,
+            v_active: mode.height,
+            v_blanking: v_blanking as u16,
+            v_sync_offset: 3,
+            v_sync_width: 5,
+            interlaced: false,
+            h_sync_positive: true,
+            v_sync_positive: false,
+        };
+        if timing.h_active > 0xFFF
+            || timing.v_active > 0xFFF
+            || timing.v_blanking > 0xFFF
+            || timing.pixel_clock_khz / 10 > u16::MAX as u32
+        {
+            return Err(EdidError::UnsupportedMode(mode));
+        }
+        Ok(timing)
+    }
+
+    /// Returns the refresh rate in Hz, 0 when the timing has no pixel at all
+    pub fn refresh_rate(&self) -> f64 {
+        let h_total = self.h_active as u32 + self.h_blanking as u32;
+        let v_total = self.v_active as u32 + self.v_blanking as u32;
+        if h_total == 0 || v_total == 0 {
+            return 0.0;
+        }
+        self.pixel_clock_khz as f64 * 1000.0 / (h_total as f64 * v_total as f64)
+    }
+
+    /// Returns the [VideoMode] of this timing, with its refresh rate rounded to the nearest Hz
+    pub fn mode(&self) -> VideoMode {
+        VideoMode::new(
+            self.h_active,
+            self.v_active,
+            self.refresh_rate().round() as u8,
+        )
+    }
+
+    fn parse(d: &[u8]) -> Option<Self> {
+        let pixel_clock = u16::from_le_bytes([d[0], d[1]]);
+        if pixel_clock == 0 {
+            return None;
+        }
+        Some(Self {
+            pixel_clock_khz: pixel_clock as u32 * 10,
+            h_active: d[2] as u16 | ((d[4] as u16 & 0xF0) << 4),
+            h_blanking: d[3] as u16 | ((d[4] as u16 & 0x0F) << 8),
+            v_active: d[5] as u16 | ((d[7] as u16 & 0xF0) << 4),
+            v_blanking: d[6] as u16 | ((d[7] as u16 & 0x0F) << 8),
+            h_sync_offset: d[8] as u16 | ((d[11] as u16 & 0xC0) << 2),
+            h_sync_width: d[9] as u16 | ((d[11] as u16 & 0x30) << 4),
+            v_sync_offset: (d[10] >> 4) | ((d[11] & 0x0C) << 2),
+            v_sync_width: (d[10] & 0x0F) | ((d[11] & 0x03) << 4),
+            interlaced: d[17] & 0x80 != 0,
+            h_sync_positive: d[17] & 0x02 != 0,
+            v_sync_positive: d[17] & 0x04 != 0,
+        })
+    }
+
+    fn encode(&self) -> [u8; 18] {
+        let mut d = [0; 18];
+        d[0..2].copy_from_slice(&((self.pixel_clock_khz / 10) as u16).to_le_bytes());
+        d[2] = self.h_active as u8;
+        d[3] = self.h_blanking as u8;
+        d[4] = ((self.h_active >> 4) as u8 & 0xF0) | ((self.h_blanking >> 8) as u8 & 0x0F);
+        d[5] = self.v_active as u8;
+        d[6] = self.v_blanking as u8;
+        d[7] = ((self.v_active >> 4) as u8 & 0xF0) | ((self.v_blanking >> 8) as u8 & 0x0F);
+        d[8] = self.h_sync_offset as u8;
+        d[9] = self.h_sync_width as u8;
+        d[10] = ((self.v_sync_offset & 0x0F) << 4) | (self.v_sync_width & 0x0F);
+        d[11] = ((self.h_sync_offset >> 2) as u8 & 0xC0)
+            | ((self.h_sync_width >> 4) as u8 & 0x30)
+            | ((self.v_sync_offset >> 2) & 0x0C)
+            | ((self.v_sync_width >> 4) & 0x03);
+        d[17] = ((self.interlaced as u8) << 7)
+            | 0x18
+            | ((self.v_sync_positive as u8) << 2)
+            | ((self.h_sync_positive as u8) << 1);
+        d
+    }
+}
+
+/// CEA-861 extension block, describing the HDMI capabilities of the input
+#[derive(PartialEq, Eq, Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CeaExtension {
+    /// Revision of the extension
+    pub revision: u8,
+    /// The sink underscans IT formats by default
+    pub underscan: bool,
+    /// The sink supports basic audio
+    pub basic_audio: bool,
+    /// The sink supports YCbCr 4:4:4
+    pub ycbcr444: bool,
+    /// The sink supports YCbCr 4:2:2
+    pub ycbcr422: bool,
+    /// Video identification codes of the supported CEA modes, without the native flag
+    pub video_codes: Vec<u8>,
+    /// Additional detailed timings
+    pub detailed_timings: Vec<DetailedTiming>,
+}
+
+impl CeaExtension {
+    fn parse(block: &[u8]) -> Self {
+        // Below 4, the offset tells the block holds neither data blocks nor detailed timings
+        let has_dtds = block[2] >= 4;
+        let dtd_offset = (block[2] as usize).clamp(4, BLOCK_LEN - 1);
+        let mut video_codes = Vec::new();
+        let mut i = 4;
+        while i < dtd_offset {
+            let tag = block[i] >> 5;
+            let len = (block[i] & 0x1F) as usize;
+            let end = (i + 1 + len).min(dtd_offset);
+            if tag == CEA_VIDEO_DATA_BLOCK {
+                video_codes.extend(block[i + 1..end].iter().map(|svd| svd & 0x7F));
+            }
+            i = end;
+        }
+
+        let detailed_timings = if has_dtds {
+            block[dtd_offset..BLOCK_LEN - 1]
+                .chunks_exact(18)
+                .map_while(DetailedTiming::parse)
+                .collect()
+        } else {
+            Vec::new()
+        };
+
+        Self {
+            revision: block[1],
+            underscan: block[3] & 0x80 != 0,
+            basic_audio: block[3] & 0x40 != 0,
+            ycbcr444: block[3] & 0x20 != 0,
+            ycbcr422: block[3] & 0x10 != 0,
+            video_codes,
+            detailed_timings,
+        }
+    }
+
+    fn encode(&self) -> [u8; BLOCK_LEN] {
+        let mut block = [0; BLOCK_LEN];
+        block[0] = CEA_TAG;
+        block[1] = self.revision;
+        block[3] = ((self.underscan as u8) << 7)
+            | ((self.basic_audio as u8) << 6)
+            | ((self.ycbcr444 as u8) << 5)
+            | ((self.ycbcr422 as u8) << 4);
+
+        let mut i = 4;
+        if !self.video_codes.is_empty() {
+            let codes = &self.video_codes[..self.video_codes.len().min(0x1F)];
+            block[i] = (CEA_VIDEO_DATA_BLOCK << 5) | codes.len() as u8;
+            block[i + 1..i + 1 + codes.len()].copy_from_slice(codes);
+            i += 1 + codes.len();
+        }
+        block[2] = i as u8;
+
+        for timing in &self.detailed_timings {
+            if i + 18 > BLOCK_LEN - 1 {
+                break;
+            }
+            block[i..i + 18].copy_from_slice(&timing.encode());
+            i += 18;
+        }
+        block[BLOCK_LEN - 1] = checksum(&block[..BLOCK_LEN - 1]);
+        block
+    }
+}
+
+/// Extended display identification data presented by the inputs of a sending card
+#[derive(PartialEq, Eq, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Edid {
+    /// Three letter PNP manufacturer ID
+    pub manufacturer: String,
+    /// Manufacturer product code
+    pub product_code: u16,
+    /// Serial number
+    pub serial_number: u32,
+    /// Week of manufacture
+    pub week: u8,
+    /// Year of manufacture
+    pub year: u16,
+    /// EDID version and revision
+    pub version: (u8, u8),
+    /// Name from the monitor name descriptor
+    pub name: Option<String>,
+    /// Timing of the native mode of the display
+    pub preferred_timing: Option<DetailedTiming>,
+    /// Modes listed in the established and standard timings
+    pub supported_modes: Vec<VideoMode>,
+    /// CEA-861 extension block, if any
+    pub cea_extension: Option<CeaExtension>,
+}
+
+impl Edid {
+    /// Builds an EDID advertising `width`x`height` at `refresh_rate` Hz as the only mode
+    pub fn custom(width: u16, height: u16, refresh_rate: u8) -> Result<Self, EdidError> {
+        let timing = DetailedTiming::reduced_blanking(VideoMode::new(width, height, refresh_rate))?;
+        Ok(Self {
+            manufacturer: "NVS".to_string(),
+            product_code: 0,
+            serial_number: 0,
+            week: 0,
+            year: 2025,
+            version: (1, 3),
+            name: Some(format!("{width}x{height}")),
+            preferred_timing: Some(timing),
+            supported_modes: Vec::new(),
+            cea_extension: Some(CeaExtension {
+                revision: 3,
+                detailed_timings: vec![timing],
+                ..Default::default()
+            }),
+        })
+    }
+
+    /// Parses an EDID base block, followed by an optional CEA-861 extension block
+    pub fn parse(bytes: &[u8]) -> Result<Self, EdidError> {
+        if bytes.len() < BLOCK_LEN {
+            return Err(EdidError::Length(bytes.len()));
+        }
+        let base = &bytes[..BLOCK_LEN];
+        if base[..8] != HEADER {
+            return Err(EdidError::Header);
+        }
+        if checksum(base) != 0 {
+            return Err(EdidError::Checksum(0));
+        }
+
+        let vendor = u16::from_be_bytes([base[8], base[9]]);
+        let manufacturer = [10, 5, 0]
+            .iter()
+            .map(|shift| (b'A' - 1 + ((vendor >> shift) & 0x1F) as u8) as char)
+            .collect();
+
+        let established = u32::from_be_bytes([0, base[35], base[36], base[37]]);
+        let mut supported_modes: Vec<VideoMode> = ESTABLISHED_TIMINGS
+            .iter()
+            .enumerate()
+            .filter(|(bit, _)| established & (0x80_0000 >> bit) != 0)
+            .map(|(_, mode)| *mode)
+            .collect();
+        supported_modes.extend(
+            base[38..54]
+                .chunks_exact(2)
+                .filter_map(|timing| parse_standard_timing(timing, base[19])),
+        );
+
+        let mut preferred_timing = None;
+        let mut name = None;
+        for descriptor in base[54..126].chunks_exact(18) {
+            if let Some(timing) = DetailedTiming::parse(descriptor) {
+                preferred_timing.get_or_insert(timing);
+            } else if descriptor[3] == MONITOR_NAME_TAG {
+                let text = descriptor[5..18]
+                    .split(|c| *c == 0x0A)
+                    .next()
+                    .unwrap_or(&[]);
+                name = Some(String::from_utf8_lossy(text).trim_end().to_string());
+            }
+        }
+
+        let mut cea_extension = None;
+        if base[126] > 0 && bytes.len() >= 2 * BLOCK_LEN {
+            let block = &bytes[BLOCK_LEN..2 * BLOCK_LEN];
+            if checksum(block) != 0 {
+                return Err(EdidError::Checksum(1));
+            }
+            if block[0] == CEA_TAG {
+                cea_extension = Some(CeaExtension::parse(block));
+            }
+        }
+
+        Ok(Self {
+            manufacturer,
+            product_code: u16::from_le_bytes([base[10], base[11]]),
+            serial_number: u32::from_le_bytes([base[12], base[13], base[14], base[15]]),
+            week: base[16],
+            year: 1990 + base[17] as u16,
+            version: (base[18], base[19]),
+            name,
+            preferred_timing,
+            supported_modes,
+            cea_extension,
+        })
+    }
+
+    /// Encodes the EDID into the 256 bytes EDID space of a sending card
+    ///
+    /// Modes which can be neither established nor standard timings are left out,
+    /// as well as any standard timing past the eighth one
+    pub fn to_bytes(&self) -> [u8; EDID_LEN] {
+        let mut bytes = [0; EDID_LEN];
+        let base = &mut bytes[..BLOCK_LEN];
+        base[..8].copy_from_slice(&HEADER);
+
+        let vendor = self.manufacturer.bytes().take(3).fold(0u16, |id, c| {
+            (id << 5) | (c.to_ascii_uppercase().wrapping_sub(b'A' - 1) as u16 & 0x1F)
+        });
+        base[8..10].copy_from_slice(&vendor.to_be_bytes());
+        base[10..12].copy_from_slice(&self.product_code.to_le_bytes());
+        base[12..16].copy_from_slice(&self.serial_number.to_le_bytes());
+        base[16] = self.week;
+        base[17] = self.year.saturating_sub(1990) as u8;
+        base[18] = self.version.0;
+        base[19] = self.version.1;
+
+        // Digital input, undefined size, gamma 2.2, RGB colour and preferred timing in first descriptor
+        base[20] = 0x80;
+        base[23] = 0x78;
+        base[24] = 0x0A;
+        // sRGB chromaticity coordinates
+        base[25..35].copy_from_slice(&[0xEE, 0x91, 0xA3, 0x54, 0x4C, 0x99, 0x26, 0x0F, 0x50, 0x54]);
+
+        let mut established = 0u32;
+        let mut standard = Vec::new();
+        for mode in &self.supported_modes {
+            if let Some(bit) = ESTABLISHED_TIMINGS.iter().position(|m| m == mode) {
+                established |= 0x80_0000 >> bit;
+            } else if let Some(timing) = encode_standard_timing(mode, self.version.1) {
+                standard.push(timing);
+            }
+        }
+        base[35..38].copy_from_slice(&established.to_be_bytes()[1..]);
+        for (i, slot) in base[38..54].chunks_exact_mut(2).enumerate() {
+            slot.copy_from_slice(standard.get(i).unwrap_or(&[0x01, 0x01]));
+        }
+
+        let mut descriptors = base[54..126].chunks_exact_mut(18);
+        if let Some(timing) = &self.preferred_timing {
+            descriptors
+                .next()
+                .unwrap()
+                .copy_from_slice(&timing.encode());
+        }
+        if let Some(name) = &self.name {
+            let descriptor = descriptors.next().unwrap();
+            descriptor[3] = MONITOR_NAME_TAG;
+            let text = &mut descriptor[5..18];
+            text.fill(0x20);
+            let len = name.len().min(13);
+            text[..len].copy_from_slice(&name.as_bytes()[..len]);
+            if len < 13 {
+                text[len] = 0x0A;
+            }
+        }
+        for descriptor in descriptors {
+            // Dummy descriptor
+            descriptor[3] = 0x10;
+        }
+
+        if let Some(cea) = &self.cea_extension {
+            base[126] = 1;
+            bytes[BLOCK_LEN..].copy_from_slice(&cea.encode());
+        }
+        bytes[BLOCK_LEN - 1] = checksum(&bytes[..BLOCK_LEN - 1]);
+        bytes
+    }
+}
+
+fn parse_standard_timing(timing: &[u8], revision: u8) -> Option<VideoMode> {
+    if timing == [0x01, 0x01] || timing[0] == 0 {
+        return None;
+    }
+    let width = (timing[0] as u16 + 31) * 8;
+    let height = match timing[1] >> 6 {
+        0 if revision < 3 => width,
+        0 => width * 10 / 16,
+        1 => width * 3 / 4,
+        2 => width * 4 / 5,
+        _ => width * 9 / 16,
+    };
+    Some(VideoMode::new(width, height, (timing[1] & 0x3F) + 60))
+}
+
+fn encode_standard_timing(mode: &VideoMode, revision: u8) -> Option<[u8; 2]> {
+    if !mode.width.is_multiple_of(8) || !(256..=2288).contains(&mode.width) {
+        return None;
+    }
+    if !(60..=123).contains(&mode.refresh_rate) {
+        return None;
+    }
+    let aspect = (0..4).find(|aspect| {
+        parse_standard_timing(&[(mode.width / 8 - 31) as u8, aspect << 6], revision)
+            .is_some_and(|m| m.height == mode.height)
+    })?;
+    Some([
+        (mode.width / 8 - 31) as u8,
+        (aspect << 6) | (mode.refresh_rate - 60),
+    ])
+}
+
+/// Returns the byte making the sum of `bytes` and itself a multiple of 256
+fn checksum(bytes: &[u8]) -> u8 {
+    bytes
+        .iter()
+        .fold(0u8, |sum, byte| sum.wrapping_add(*byte))
+        .wrapping_neg()
+}
@@ -0,0 +1,70 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+/// Interval between two brightness writes of a fade, 25 per second
+pub(crate) const FADE_STEP: Duration = Duration::from_millis(40);
+
+/// Interval between two brightness writes of a fade through a web API, 4 per second, as each write
+/// is an HTTP request per screen
+#[cfg(feature = "h-series")]
+pub(crate) const HTTP_FADE_STEP: Duration = Duration::from_millis(250);
+
+/// Gamma used by [FadeCurve::Perceptual] to turn brightness into perceived lightness
+const GAMMA: f64 = 2.2;
+
+/// How the brightness moves between its start and its target during a fade
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum FadeCurve {
+    /// Constant change of the brightness value
+    #[default]
+    Linear,
+    /// Starts and ends slowly, faster in the middle
+    Ease,
+    /// Constant change of the perceived lightness, gamma corrected
+    Perceptual,
+}
+
+impl FadeCurve {
+    /// Returns the brightness after `progress` of a fade from `from` to `to`, with `progress` from 0 to 1
+    pub fn brightness_at(self, from: u8, to: u8, progress: f64) -> u8 {
+        let progress = progress.clamp(0.0, 1.0);
+        let (from, to) = (from as f64 / 255.0, to as f64 / 255.0);
+        let level = match self {
+            FadeCurve::Linear => from + (to - from) * progress,
+            FadeCurve::Ease => {
+                let eased = progress * progress * (3.0 - 2.0 * progress);
+                from + (to - from) * eased
+            }
+            FadeCurve::Perceptual => {
+                let (from, to) = (from.powf(1.0 / GAMMA), to.powf(1.0 / GAMMA));
+                (from + (to - from) * progress).powf(GAMMA)
+            }
+        };
+        (level * 255.0).round() as u8
+    }
+}
+
+/// Stops a running fade from another thread
+///
+/// Clones share the same state, cancelling one cancels the fades watching any of them
+#[derive(Clone, Debug, Default)]
+pub struct CancelFade(Arc<AtomicBool>);
+
+impl CancelFade {
+    /// Creates a handle which is not cancelled
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Stops the fades watching this handle, leaving the brightness where they are
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    /// Returns whether [CancelFade::cancel] was called
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
@@ -0,0 +1,281 @@
+use std::io;
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::controller::{Controller, Error};
+use crate::descriptor::ControllerDescriptor;
+use crate::fade::{CancelFade, FadeCurve};
+use crate::types::DisplayState;
+
+/// How long each member of a [ControllerGroup] is given to complete an operation by default
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// State of a [Controller] returned by [ControllerGroup::status]
+#[derive(PartialEq, Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ControllerStatus {
+    /// Global brightness
+    pub brightness: u8,
+    /// What the screen is showing, [DisplayState::Freeze] being inferred as explained in
+    /// [Controller::display_state]
+    pub display_state: DisplayState,
+}
+
+/// Outcome of an operation on one member of a [ControllerGroup]
+#[derive(Debug)]
+pub struct MemberResult<T> {
+    /// Member the operation ran on
+    pub descriptor: ControllerDescriptor,
+    /// Value returned by the member, or why the operation failed on it
+    pub result: Result<T, Error>,
+}
+
+/// Outcome of an operation on every member of a [ControllerGroup], in the order of the members
+#[derive(Debug)]
+pub struct GroupReport<T> {
+    /// Outcome on each member
+    pub members: Vec<MemberResult<T>>,
+}
+
+impl<T> GroupReport<T> {
+    /// Returns whether the operation succeeded on every member
+    pub fn all_ok(&self) -> bool {
+        self.members.iter().all(|member| member.result.is_ok())
+    }
+
+    /// Returns the members the operation succeeded on, with the value they returned
+    pub fn successes(&self) -> impl Iterator<Item = (&ControllerDescriptor, &T)> {
+        self.members.iter().filter_map(|member| {
+            member
+                .result
+                .as_ref()
+                .ok()
+                .map(|value| (&member.descriptor, value))
+        })
+    }
+
+    /// Returns the members the operation failed on, with the error
+    pub fn failures(&self) -> impl Iterator<Item = (&ControllerDescriptor, &Error)> {
+        self.members.iter().filter_map(|member| {
+            member
+                .result
+                .as_ref()
+                .err()
+                .map(|err| (&member.descriptor, err))
+        })
+    }
+}
+
+/// A [Controller] of a group, shared with the threads running operations on it
+#[derive(Debug)]
+struct Member {
+    descriptor: ControllerDescriptor,
+    controller: Arc<Mutex<Controller>>,
+}
+
+/// Several [Controller]s driven together, such as every sending card of a show
+///
+/// Operations run on every member in parallel. A member failing or not answering within the
+/// timeout does not stop the others, its error is returned in the [GroupReport]
+#[derive(Debug)]
+pub struct ControllerGroup {
+    members: Vec<Member>,
+    timeout: Duration,
+}
+
+impl ControllerGroup {
+    /// Builds a group from connected controllers
+    pub fn new(controllers: impl IntoIterator<Item = Controller>) -> Self {
+        let mut group = Self {
+            members: Vec::new(),
+            timeout: DEFAULT_TIMEOUT,
+        };
+        for controller in controllers {
+            group.push(controller);
+        }
+        group
+    }
+
+    /// Builds a group from every controller found by [crate::discover]
+    pub fn discover() -> Result<Self, io::Error> {
+        Ok(Self::new(crate::discover()?))
+    }
+
+    /// Connects to the described controllers in parallel
+    ///
+    /// The group holds the controllers which could be reached, the report tells how the connexion
+    /// to each of them went
+    pub fn connect(descriptors: &[ControllerDescriptor]) -> (Self, GroupReport<()>) {
+        let connexions: Vec<_> = thread::scope(|scope| {
+            let handles: Vec<_> = descriptors
+                .iter()
+                .map(|descriptor| scope.spawn(|| descriptor.connect()))
+                .collect();
+            handles
+                .into_iter()
+                .map(|handle| handle.join().unwrap_or(Err(Error::Panicked)))
+                .collect()
+        });
+
+        let mut group = Self::new([]);
+        let mut members = Vec::with_capacity(descriptors.len());
+        for (descriptor, connexion) in descriptors.iter().zip(connexions) {
+            let result = connexion.map(|controller| group.push(controller));
+            members.push(MemberResult {
+                descriptor: descriptor.clone(),
+                result,
+            });
+        }
+        (group, GroupReport { members })
+    }
+
+    /// Adds a controller to the group
+    pub fn push(&mut self, controller: Controller) {
+        self.members.push(Member {
+            descriptor: controller.descriptor(),
+            controller: Arc::new(Mutex::new(controller)),
+        });
+    }
+
+    /// Returns the number of controllers in the group
+    pub fn len(&self) -> usize {
+        self.members.len()
+    }
+
+    /// Returns whether the group has no controller
+    pub fn is_empty(&self) -> bool {
+        self.members.is_empty()
+    }
+
+    /// Returns a description of each member, in the order of the members
+    pub fn descriptors(&self) -> impl Iterator<Item = &ControllerDescriptor> {
+        self.members.iter().map(|member| &member.descriptor)
+    }
+
+    /// Returns how long each member is given to complete an operation
+    pub fn timeout(&self) -> Duration {
+        self.timeout
+    }
+
+    /// Sets how long each member is given to complete an operation
+    ///
+    /// A member running late is reported with an [Error::Timeout], and completes the operation in
+    /// the background before running the next one
+    pub fn set_timeout(&mut self, timeout: Duration) {
+        self.timeout = timeout;
+    }
+
+    /// Runs `operation` on every member in parallel, and returns its outcome on each of them
+    pub fn run<T, F>(&self, operation: F) -> GroupReport<T>
+    where
+        T: Send + 'static,
+        F: Fn(&mut Controller) -> Result<T, Error> + Send + Sync + 'static,
+    {
+        self.run_within(self.timeout, operation)
+    }
+
+    /// Runs `operation` on every member in parallel, giving each of them `timeout` to complete it
+    fn run_within<T, F>(&self, timeout: Duration, operation: F) -> GroupReport<T>
+    where
+        T: Send + 'static,
+        F: Fn(&mut Controller) -> Result<T, Error> + Send + Sync + 'static,
+    {
+        let operation = Arc::new(operation);
+        let receivers: Vec<_> = self
+            .members
+            .iter()
+            .map(|member| {
+                let operation = Arc::clone(&operation);
+                let controller = Arc::clone(&member.controller);
+                let (sender, receiver) = mpsc::channel();
+                thread::spawn(move || {
+                    let mut controller = controller.lock().unwrap_or_else(PoisonError::into_inner);
+                    // The group stops listening once the timeout has elapsed
+                    let _ = sender.send(operation(&mut controller));
+                });
+                receiver
+            })
+            .collect();
+
+        let deadline = Instant::now() + timeout;
+        let members = self
+            .members
+            .iter()
+            .zip(receivers)
+            .map(|(member, receiver)| {
+                let remaining = deadline.saturating_duration_since(Instant::now());
+                let result = match receiver.recv_timeout(remaining) {
+                    Ok(result) => result,
+                    Err(RecvTimeoutError::Timeout) => Err(Error::Timeout(timeout)),
+                    // The sender is only dropped without sending when the operation panicked
+                    Err(RecvTimeoutError::Disconnected) => Err(Error::Panicked),
+                };
+                MemberResult {
+                    descriptor: member.descriptor.clone(),
+                    result,
+                }
+            })
+            .collect();
+        GroupReport { members }
+    }
+
+    /// Sets the global brightness of every member
+    pub fn set_brightness(&self, value: u8) -> GroupReport<()> {
+        self.run(move |controller| controller.set_brightness(value))
+    }
+
+    /// Fades the global brightness of every member to `target` in lockstep, see [Controller::fade_brightness]
+    pub fn fade_brightness(
+        &self,
+        target: u8,
+        duration: Duration,
+        curve: FadeCurve,
+    ) -> GroupReport<u8> {
+        self.fade_brightness_cancellable(target, duration, curve, &CancelFade::new())
+    }
+
+    /// Fades the global brightness of every member to `target` in lockstep, until `cancel` stops it
+    ///
+    /// Every member follows `curve` from its own brightness, measuring the progress from the same
+    /// instant. Each member is given the duration of the fade on top of the group timeout
+    pub fn fade_brightness_cancellable(
+        &self,
+        target: u8,
+        duration: Duration,
+        curve: FadeCurve,
+        cancel: &CancelFade,
+    ) -> GroupReport<u8> {
+        let start = Instant::now();
+        let cancel = cancel.clone();
+        self.run_within(duration + self.timeout, move |controller| {
+            controller.fade_brightness_from(start, target, duration, curve, &cancel)
+        })
+    }
+
+    /// Turns the screens of every member off, see [Controller::blackout]
+    pub fn blackout(&self) -> GroupReport<()> {
+        self.run(Controller::blackout)
+    }
+
+    /// Shows the input video again on every member, see [Controller::normal]
+    pub fn normal(&self) -> GroupReport<()> {
+        self.run(Controller::normal)
+    }
+
+    /// Returns the brightness and display state of every member
+    pub fn status(&self) -> GroupReport<ControllerStatus> {
+        self.run(|controller| {
+            Ok(ControllerStatus {
+                brightness: controller.brightness()?,
+                display_state: controller.display_state()?,
+            })
+        })
+    }
+
+    /// Stores the current parameters of every member, see [Controller::save_to_hardware]
+    pub fn save_to_hardware(&self) -> GroupReport<()> {
+        self.run(Controller::save_to_hardware)
+    }
+}
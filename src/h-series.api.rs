@@ -1,3 +0,0 @@
-//http://192.168.0.10/api/screen/writeBrightness
-
-//{"brightness":46,"screenId":0,"deviceId":0}
\ No newline at end of file
@@ -0,0 +1,447 @@
+//! This crate contains the HTTP client for the web API of Novastar H Series processors
+//!
+//! H Series processors answer the binary protocol for discovery, but their screens are
+//! driven through `POST /api/...` requests carrying JSON bodies
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use thiserror::Error;
+use ureq::Agent;
+
+/// Errors returned by the web API of an H Series processor
+#[derive(Error, Debug)]
+pub enum HSeriesError {
+    /// The request could not be sent or the response could not be read
+    #[error("http error: {0}")]
+    Http(#[from] ureq::Error),
+    /// The processor answered with an unexpected HTTP status
+    #[error("unexpected http status {0}")]
+    Status(u16),
+    /// The processor rejected the request
+    #[error("request rejected with status {status}: {message}")]
+    Api {
+        /// Status code returned by the processor
+        status: i64,
+        /// Message returned by the processor
+        message: String,
+    },
+    /// The processor accepted a request but its reply carries no data
+    #[error("no data in reply to {0}")]
+    MissingData(String),
+    /// The screen has no layer with that ID
+    #[error("layer {0} does not exist")]
+    UnknownLayer(u32),
+}
+
+/// Screen configured on an H Series processor
+#[derive(PartialEq, Eq, Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Screen {
+    /// ID of the screen, used to address it in requests
+    pub screen_id: u32,
+    /// Name given to the screen
+    pub name: String,
+    /// Brightness of the screen in percent
+    pub brightness: u8,
+}
+
+/// Identification of an H Series processor
+#[derive(PartialEq, Eq, Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeviceInfo {
+    /// ID of the processor in a cascade
+    pub device_id: u32,
+    /// Name given to the processor
+    pub name: String,
+    /// Model ID of the processor, as reported by the binary protocol
+    pub model_id: u16,
+    /// Serial number of the processor
+    pub serial_number: String,
+    /// Firmware version of the processor
+    pub firmware_version: String,
+}
+
+/// Preset stored on an H Series processor
+#[derive(PartialEq, Eq, Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Preset {
+    /// ID of the preset, used to load it
+    pub preset_id: u32,
+    /// Name given to the preset
+    pub name: String,
+}
+
+/// Video input of an H Series processor
+#[derive(PartialEq, Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Input {
+    /// ID of the input, used as a layer source
+    pub input_id: u32,
+    /// Name given to the input
+    pub name: String,
+    /// A valid signal is connected to the input
+    pub has_signal: bool,
+    /// Horizontal resolution of the signal, `0` without signal
+    #[serde(default)]
+    pub width: u16,
+    /// Vertical resolution of the signal, `0` without signal
+    #[serde(default)]
+    pub height: u16,
+    /// Refresh rate of the signal in Hz, `0` without signal
+    #[serde(default)]
+    pub refresh_rate: f32,
+}
+
+/// Position and size of a layer on its screen, in pixels
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Window {
+    /// Horizontal position of the left edge
+    pub x: i32,
+    /// Vertical position of the top edge
+    pub y: i32,
+    /// Width
+    pub width: u32,
+    /// Height
+    pub height: u32,
+}
+
+/// Layer showing an input on a screen
+#[derive(PartialEq, Eq, Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Layer {
+    /// ID of the layer on its screen
+    pub layer_id: u32,
+    /// ID of the input shown by the layer
+    pub input_id: u32,
+    /// Position and size of the layer
+    pub window: Window,
+}
+
+/// Envelope of every response of the web API
+#[derive(Deserialize)]
+struct Reply<T> {
+    status: i64,
+    #[serde(default)]
+    msg: String,
+    data: Option<T>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct DeviceRequest {
+    device_id: u32,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ScreenRequest {
+    screen_id: u32,
+    device_id: u32,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct BrightnessRequest {
+    brightness: u8,
+    screen_id: u32,
+    device_id: u32,
+}
+
+#[derive(Deserialize)]
+struct Brightness {
+    brightness: u8,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PresetRequest {
+    preset_id: u32,
+    screen_id: u32,
+    device_id: u32,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct CreateLayerRequest {
+    input_id: u32,
+    window: Window,
+    screen_id: u32,
+    device_id: u32,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CreatedLayer {
+    layer_id: u32,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct LayerWindowRequest {
+    layer_id: u32,
+    window: Window,
+    screen_id: u32,
+    device_id: u32,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct LayerSourceRequest {
+    layer_id: u32,
+    input_id: u32,
+    screen_id: u32,
+    device_id: u32,
+}
+
+/// Client for the web API of an H Series processor
+#[derive(Debug, Clone)]
+pub struct HSeriesClient {
+    base_url: String,
+    device_id: u32,
+    agent: Agent,
+}
+
+impl HSeriesClient {
+    /// Creates a client for the processor at `host`, with an optional `:port`
+    pub fn new(host: &str) -> Self {
+        let agent = Agent::config_builder()
+            .timeout_global(Some(Duration::from_secs(2)))
+            .http_status_as_error(false)
+            .build()
+            .into();
+        Self {
+            base_url: format!("http://{host}"),
+            device_id: 0,
+            agent,
+        }
+    }
+
+    /// Addresses the processor with `device_id` in a cascade, `0` being the first one
+    pub fn with_device_id(mut self, device_id: u32) -> Self {
+        self.device_id = device_id;
+        self
+    }
+
+    /// Returns the identification of the processor
+    pub fn device_info(&self) -> Result<DeviceInfo, HSeriesError> {
+        self.request(
+            "device/readDetail",
+            &DeviceRequest {
+                device_id: self.device_id,
+            },
+        )
+    }
+
+    /// Returns the screens configured on the processor
+    pub fn screens(&self) -> Result<Vec<Screen>, HSeriesError> {
+        self.request(
+            "screen/readAllList",
+            &DeviceRequest {
+                device_id: self.device_id,
+            },
+        )
+    }
+
+    /// Returns the brightness of a screen in percent
+    pub fn brightness(&self, screen_id: u32) -> Result<u8, HSeriesError> {
+        self.request::<Brightness>(
+            "screen/readBrightness",
+            &ScreenRequest {
+                screen_id,
+                device_id: self.device_id,
+            },
+        )
+        .map(|reply| reply.brightness)
+    }
+
+    /// Sets the brightness of a screen in percent
+    pub fn set_brightness(&self, screen_id: u32, brightness: u8) -> Result<(), HSeriesError> {
+        self.send(
+            "screen/writeBrightness",
+            &BrightnessRequest {
+                brightness: brightness.min(100),
+                screen_id,
+                device_id: self.device_id,
+            },
+        )
+    }
+
+    /// Returns the presets stored for a screen
+    pub fn presets(&self, screen_id: u32) -> Result<Vec<Preset>, HSeriesError> {
+        self.request(
+            "preset/readAllList",
+            &ScreenRequest {
+                screen_id,
+                device_id: self.device_id,
+            },
+        )
+    }
+
+    /// Loads a preset on a screen
+    pub fn load_preset(&self, screen_id: u32, preset_id: u32) -> Result<(), HSeriesError> {
+        self.send(
+            "preset/load",
+            &PresetRequest {
+                preset_id,
+                screen_id,
+                device_id: self.device_id,
+            },
+        )
+    }
+
+    /// Returns the video inputs of the processor and their signal status
+    pub fn inputs(&self) -> Result<Vec<Input>, HSeriesError> {
+        self.request(
+            "input/readAllList",
+            &DeviceRequest {
+                device_id: self.device_id,
+            },
+        )
+    }
+
+    /// Returns the layers of a screen
+    pub fn layers(&self, screen_id: u32) -> Result<Vec<Layer>, HSeriesError> {
+        self.request(
+            "layer/readAllList",
+            &ScreenRequest {
+                screen_id,
+                device_id: self.device_id,
+            },
+        )
+    }
+
+    /// Creates a layer showing `input_id` on a screen and returns its ID
+    pub fn create_layer(
+        &self,
+        screen_id: u32,
+        input_id: u32,
+        window: Window,
+    ) -> Result<u32, HSeriesError> {
+        self.request::<CreatedLayer>(
+            "layer/create",
+            &CreateLayerRequest {
+                input_id,
+                window,
+                screen_id,
+                device_id: self.device_id,
+            },
+        )
+        .map(|layer| layer.layer_id)
+    }
+
+    /// Moves and resizes a layer
+    pub fn set_layer_window(
+        &self,
+        screen_id: u32,
+        layer_id: u32,
+        window: Window,
+    ) -> Result<(), HSeriesError> {
+        self.send(
+            "layer/writeWindow",
+            &LayerWindowRequest {
+                layer_id,
+                window,
+                screen_id,
+                device_id: self.device_id,
+            },
+        )
+    }
+
+    /// Moves a layer, keeping its size
+    pub fn move_layer(
+        &self,
+        screen_id: u32,
+        layer_id: u32,
+        x: i32,
+        y: i32,
+    ) -> Result<(), HSeriesError> {
+        let window = self.layer_window(screen_id, layer_id)?;
+        self.set_layer_window(screen_id, layer_id, Window { x, y, ..window })
+    }
+
+    /// Resizes a layer, keeping its position
+    pub fn resize_layer(
+        &self,
+        screen_id: u32,
+        layer_id: u32,
+        width: u32,
+        height: u32,
+    ) -> Result<(), HSeriesError> {
+        let window = self.layer_window(screen_id, layer_id)?;
+        self.set_layer_window(
+            screen_id,
+            layer_id,
+            Window {
+                width,
+                height,
+                ..window
+            },
+        )
+    }
+
+    /// Switches the input shown by a layer
+    pub fn set_layer_source(
+        &self,
+        screen_id: u32,
+        layer_id: u32,
+        input_id: u32,
+    ) -> Result<(), HSeriesError> {
+        self.send(
+            "layer/writeSource",
+            &LayerSourceRequest {
+                layer_id,
+                input_id,
+                screen_id,
+                device_id: self.device_id,
+            },
+        )
+    }
+
+    fn layer_window(&self, screen_id: u32, layer_id: u32) -> Result<Window, HSeriesError> {
+        self.layers(screen_id)?
+            .into_iter()
+            .find(|layer| layer.layer_id == layer_id)
+            .map(|layer| layer.window)
+            .ok_or(HSeriesError::UnknownLayer(layer_id))
+    }
+
+    /// Sends a request whose reply carries no data
+    fn send(&self, path: &str, body: &impl Serialize) -> Result<(), HSeriesError> {
+        self.reply::<serde_json::Value>(path, body).map(|_| ())
+    }
+
+    /// Sends a request and returns the data of its reply
+    fn request<T: DeserializeOwned>(
+        &self,
+        path: &str,
+        body: &impl Serialize,
+    ) -> Result<T, HSeriesError> {
+        self.reply(path, body)?
+            .ok_or_else(|| HSeriesError::MissingData(path.to_string()))
+    }
+
+    #[tracing::instrument(level = "debug", skip(self, body), fields(host = %self.base_url), err)]
+    fn reply<T: DeserializeOwned>(
+        &self,
+        path: &str,
+        body: &impl Serialize,
+    ) -> Result<Option<T>, HSeriesError> {
+        let mut response = self
+            .agent
+            .post(&format!("{}/api/{path}", self.base_url))
+            .send_json(body)?;
+        if !response.status().is_success() {
+            return Err(HSeriesError::Status(response.status().as_u16()));
+        }
+        let reply: Reply<T> = response.body_mut().read_json()?;
+        if reply.status != 0 {
+            return Err(HSeriesError::Api {
+                status: reply.status,
+                message: reply.msg,
+            });
+        }
+        Ok(reply.data)
+    }
+}
@@ -0,0 +1,34 @@
+/// HDR10 and HLG settings of a [crate::Controller]
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct HdrSettings {
+    /// HDR inputs are tone mapped for the screen
+    pub enabled: bool,
+    /// Peak luminance of the screen used for tone mapping, in nits
+    pub screen_peak_luminance: u16,
+    /// Ambient light level the tone mapping compensates for
+    pub ambient_light: u8,
+    /// Minimum luminance applied to HDR10 inputs
+    pub hdr10_min_luminance: u8,
+    /// HLG model applied to HLG inputs
+    pub hlg_model: u8,
+}
+
+impl HdrSettings {
+    /// Decodes the HDR information block: enable flag, peak luminance and ambient light
+    pub(crate) fn decode(info: &[u8], hdr10_min_luminance: u8, hlg_model: u8) -> Self {
+        Self {
+            enabled: info[0] != 0,
+            screen_peak_luminance: u16::from_le_bytes([info[1], info[2]]),
+            ambient_light: info[3],
+            hdr10_min_luminance,
+            hlg_model,
+        }
+    }
+
+    /// Encodes the HDR information block
+    pub(crate) fn encode_info(&self) -> [u8; 4] {
+        let [peak0, peak1] = self.screen_peak_luminance.to_le_bytes();
+        [self.enabled as u8, peak0, peak1, self.ambient_light]
+    }
+}
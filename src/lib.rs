@@ -1,14 +1,53 @@
 #![warn(missing_docs)]
 //! novastar-core is a crate used for interacting with Novastar LED Screen processors
+//!
+//! Diagnostics are emitted through [tracing], with a span per [Controller] and per request,
+//! and the bytes of every packet at the trace level
 
+pub mod capture;
+pub mod dmx;
+#[cfg(feature = "h-series")]
+pub mod h_series;
 pub mod net;
 pub mod serial;
 
+mod abl;
+mod backup;
+mod capabilities;
+mod clock;
 mod controller;
+mod descriptor;
+mod display;
+mod edid;
+mod fade;
+mod group;
+mod hdr;
 mod novastarpacket;
+mod redundancy;
+mod retry;
+mod sync;
 mod types;
+mod video;
 
+pub use crate::abl::AblSettings;
+pub use crate::backup::{BackupError, ConfigBackup};
+pub use crate::capabilities::{Capabilities, Feature, PortAddressFamily, ProtocolFamily};
+pub use crate::clock::clock_drifts;
 pub use crate::controller::*;
+pub use crate::descriptor::{ControllerDescriptor, Transport};
+pub use crate::display::{CabinetRotation, MirrorMode, ThreeDSettings};
+pub use crate::edid::{CeaExtension, DetailedTiming, EDID_LEN, Edid, EdidError, VideoMode};
+pub use crate::fade::{CancelFade, FadeCurve};
+pub use crate::group::{ControllerGroup, ControllerStatus, GroupReport, MemberResult};
+pub use crate::hdr::HdrSettings;
+pub use crate::novastarpacket::{NovastarPacket, PacketError, REQUEST, RESPONSE, hex};
+pub use crate::redundancy::{PortBackupPair, PortRole, RedundancyState};
+pub use crate::retry::RetryPolicy;
+pub use crate::sync::{FieldRateMode, SyncSettings};
+pub use crate::types::{
+    DeviceType, DisplayState, OpCode, SenderCardType, TestPattern, UnknownModel,
+};
+pub use crate::video::{Mosaic, Rect, ScalingMode, VideoProcessing};
 
 /// Returns an Iterator over the Novastar [Controller]s available on the network and on serial ports of the machine
 ///
@@ -16,12 +55,3 @@ pub use crate::controller::*;
 pub fn discover() -> Result<impl Iterator<Item = Controller>, std::io::Error> {
     Ok(net::discover()?.chain(serial::discover()?))
 }
-
-#[cfg(feature = "debug")]
-fn print_bytes(prefix: &str, buf: &[u8]) {
-    print!("{prefix} Content: ");
-    for i in buf {
-        print!("{:01$x} ", i, 2);
-    }
-    println!();
-}
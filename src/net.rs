@@ -5,6 +5,7 @@ use std::{
     net::UdpSocket,
     time::Duration,
 };
+use tracing::debug;
 
 use crate::
     controller::Controller
@@ -48,8 +49,12 @@ impl Iterator for DiscoverIter {
         };
 
         rx_host.set_port(5200);
-        let Ok(controller) = Controller::try_from_tcp_addr(rx_host) else {
-            return self.next()
+        let controller = match Controller::try_from_tcp_addr(rx_host) {
+            Ok(controller) => controller,
+            Err(err) => {
+                debug!(%rx_host, %err, "discovered host did not answer");
+                return self.next()
+            }
         };
 
         Some(controller)
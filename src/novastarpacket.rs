@@ -1,38 +1,83 @@
 use crate::types::*;
 use num_enum::{TryFromPrimitive, TryFromPrimitiveError};
+use std::fmt::{self, Debug, Display};
+use std::sync::atomic::{AtomicU8, Ordering};
 use thiserror::Error;
+use tracing::{Span, trace, warn};
 
-static mut PACKET_SERIAL: u8 = 0;
+static PACKET_SERIAL: AtomicU8 = AtomicU8::new(0);
 static MY_ADDR: u8 = 0xFE;
 
-#[derive(Debug)]
+/// Direction of the packets sent to a controller
+pub const REQUEST: u16 = 0x55AA;
+/// Direction of the packets sent back by a controller
+pub const RESPONSE: u16 = 0xAA55;
+
+/// Frame of the Novastar binary protocol
+///
+/// Both [Display] and [Debug] render it as labelled fields, with the register name, the decoded
+/// value and the checksum status, `{:#}` puts each field on its own line
 pub struct NovastarPacket<'a> {
+    /// [REQUEST] or [RESPONSE]
     pub direction: u16,
+    /// Acknowledgement status set by the controller
     pub ack: u8,
+    /// Sequence number of the request, echoed in its response
     pub serial: u8,
+    /// Address of the sender
     pub src_addr: u8,
+    /// Address of the receiver, `0xFF` for a broadcast
     pub dst_addr: u8,
+    /// Kind of card the packet is meant for
     pub device_type: DeviceType,
+    /// Output port of the receiving card, `0xFF` for every port
     pub port_addr: u8,
+    /// Receiving card behind the output port, `0xFFFF` for every card
     pub scanboard_addr: u16,
+    /// Read or write
     pub op_code: OpCode,
+    /// Unused
     pub reserved2: u8,
-    pub address: FeatureAddress,
+    /// Register address, see [NovastarPacket::address_name]
+    pub address: u32,
+    /// Number of bytes read or written
+    pub data_len: u16,
+    /// Bytes carried by the frame, empty for read requests
     pub data: &'a [u8],
+    /// Checksum carried by the frame, computed when encoding built packets
+    pub checksum: u16,
 }
 
+/// Errors returned while decoding a [NovastarPacket]
 #[derive(Error, Debug)]
 pub enum PacketError {
+    /// The frame is shorter than its header announces
+    #[error("truncated frame of {0} bytes")]
+    Truncated(usize),
+    /// The device type byte is unknown
     #[error("unknown device type: {0}")]
     DeviceType(#[from] TryFromPrimitiveError<DeviceType>),
+    /// The operation code byte is unknown
     #[error("unknown operation code: {0}")]
     OpCode(#[from] TryFromPrimitiveError<OpCode>),
-    #[error("unknown feature address: {0}")]
-    FeatureAddress(#[from] TryFromPrimitiveError<FeatureAddress>),
+    /// The checksum of the frame does not match its content
     #[error("invalid checksum {0}, should be {1}")]
     Checksum(u16, u16),
 }
 
+/// Returns the length of the frame starting at `buff`, or [None] while its header is incomplete
+///
+/// Read requests only carry the number of bytes to read, every other frame carries its data
+pub fn frame_len(buff: &[u8]) -> Option<usize> {
+    if buff.len() < 18 {
+        return None;
+    }
+    let data_len = u16::from_le_bytes([buff[16], buff[17]]) as usize;
+    let is_read_request =
+        u16::from_be_bytes([buff[0], buff[1]]) == REQUEST && buff[10] == u8::from(OpCode::Read);
+    Some(if is_read_request { 20 } else { 20 + data_len })
+}
+
 impl NovastarPacket<'_> {
     fn encode(self) -> Vec<u8> {
         let mut out: Vec<u8> = Vec::with_capacity(20);
@@ -46,38 +91,46 @@ impl NovastarPacket<'_> {
         out.extend_from_slice(&self.scanboard_addr.to_le_bytes()); // ff ff
         out.push(self.op_code as u8); // 01
         out.push(self.reserved2); // 00
-        out.extend_from_slice(&(self.address as u32).to_le_bytes()); // 01 00 00 02
+        out.extend_from_slice(&self.address.to_le_bytes()); // 01 00 00 02
         out.extend_from_slice(&(self.data.len() as u16).to_le_bytes()); // 01 00
 
-        let mut check_len = 18;
         if self.op_code == OpCode::Write {
             out.extend_from_slice(self.data);
-            check_len += self.data.len();
         }
 
-        let mut checksum: u16 = 0x5555;
-        for byte in out[2..check_len].iter() {
-            checksum += *byte as u16;
-        }
+        out.extend_from_slice(&self.expected_checksum().to_le_bytes());
 
-        out.extend_from_slice(&checksum.to_le_bytes());
-        unsafe {
-            PACKET_SERIAL += 1;
-            if PACKET_SERIAL == 255 {
-                PACKET_SERIAL = 0;
-            }
-        }
-
-        #[cfg(feature = "debug")]
-        crate::print_bytes("encode: ", &out);
+        Span::current().record("serial", self.serial);
+        trace!(bytes = %hex(&out), "encoded packet");
         out
     }
 
-    pub fn decode(buff: &[u8]) -> Result<NovastarPacket, PacketError> {
-        #[cfg(feature = "debug")]
-        crate::print_bytes("decode: ", buff);
-        let data_len: u16 = u16::from_le_bytes([buff[16], buff[17]]);
-        let out: NovastarPacket = NovastarPacket {
+    /// Decodes the frame at the start of `buff`, checking its checksum
+    pub fn decode(buff: &[u8]) -> Result<NovastarPacket<'_>, PacketError> {
+        trace!(bytes = %hex(buff), "decoding packet");
+        let packet = Self::parse(buff)?;
+        if packet.checksum_ok() {
+            Ok(packet)
+        } else {
+            warn!(
+                received = packet.checksum,
+                expected = packet.expected_checksum(),
+                "invalid checksum"
+            );
+            Err(PacketError::Checksum(
+                packet.checksum,
+                packet.expected_checksum(),
+            ))
+        }
+    }
+
+    /// Decodes the frame at the start of `buff` without checking its checksum, see
+    /// [NovastarPacket::checksum_ok]
+    pub fn parse(buff: &[u8]) -> Result<NovastarPacket<'_>, PacketError> {
+        let frame_len = frame_len(buff)
+            .filter(|len| buff.len() >= *len)
+            .ok_or(PacketError::Truncated(buff.len()))?;
+        Ok(NovastarPacket {
             direction: u16::from_be_bytes([buff[0], buff[1]]),
             ack: buff[2],
             serial: buff[3],
@@ -85,46 +138,176 @@ impl NovastarPacket<'_> {
             dst_addr: buff[5],
             device_type: DeviceType::try_from_primitive(buff[6])?,
             port_addr: buff[7],
-            scanboard_addr: u16::from_be_bytes([buff[8], buff[9]]),
+            scanboard_addr: u16::from_le_bytes([buff[8], buff[9]]),
             op_code: OpCode::try_from_primitive(buff[10])?,
-            reserved2: 0x00,
-            address: FeatureAddress::try_from_primitive(u32::from_le_bytes([
-                buff[12], buff[13], buff[14], buff[15],
-            ]))?,
-            data: &buff[18..data_len as usize + 18],
+            reserved2: buff[11],
+            address: u32::from_le_bytes([buff[12], buff[13], buff[14], buff[15]]),
+            data_len: u16::from_le_bytes([buff[16], buff[17]]),
+            data: &buff[18..frame_len - 2],
+            checksum: u16::from_le_bytes([buff[frame_len - 2], buff[frame_len - 1]]),
+        })
+    }
+
+    /// Returns the checksum matching the content of the frame
+    pub fn expected_checksum(&self) -> u16 {
+        let header = [
+            self.ack,
+            self.serial,
+            self.src_addr,
+            self.dst_addr,
+            self.device_type.into(),
+            self.port_addr,
+            self.op_code.into(),
+            self.reserved2,
+        ];
+        header
+            .iter()
+            .chain(&self.scanboard_addr.to_le_bytes())
+            .chain(&self.address.to_le_bytes())
+            .chain(&self.data_len.to_le_bytes())
+            .chain(self.data)
+            .fold(0x5555, |checksum: u16, byte| {
+                checksum.wrapping_add(*byte as u16)
+            })
+    }
+
+    /// Returns `true` when the checksum carried by the frame matches its content
+    pub fn checksum_ok(&self) -> bool {
+        self.checksum == self.expected_checksum()
+    }
+
+    /// Returns the name of the register at [NovastarPacket::address], when this crate knows it
+    pub fn address_name(&self) -> Option<String> {
+        register_name(self.device_type, self.address)
+    }
+
+    /// Returns the labelled fields shown by [Display] and [Debug]
+    fn fields(&self) -> Vec<(&'static str, String)> {
+        let direction = match self.direction {
+            REQUEST => "request".to_string(),
+            RESPONSE => "response".to_string(),
+            other => format!("{other:#06x}"),
+        };
+        let address = match self.address_name() {
+            Some(name) => format!("{name} ({:#010x})", self.address),
+            None => format!("{:#010x}", self.address),
+        };
+        let checksum = if self.checksum_ok() {
+            format!("{:#06x} ok", self.checksum)
+        } else {
+            format!(
+                "{:#06x} bad, expected {:#06x}",
+                self.checksum,
+                self.expected_checksum()
+            )
         };
-        let rx_checksum: u16 =
-            u16::from_le_bytes([buff[18 + data_len as usize], buff[19 + data_len as usize]]);
-        let mut checksum: u16 = 0x5555;
 
-        for byte in buff[2..(out.data.len() + 18)].iter() {
-            checksum += *byte as u16;
+        let mut fields = vec![
+            ("direction", direction),
+            ("ack", format!("{:#04x}", self.ack)),
+            ("serial", self.serial.to_string()),
+            ("src", format!("{:#04x}", self.src_addr)),
+            ("dst", format!("{:#04x}", self.dst_addr)),
+            ("device", format!("{:?}", self.device_type)),
+            ("port", format!("{:#04x}", self.port_addr)),
+            ("scanboard", format!("{:#06x}", self.scanboard_addr)),
+            ("op", format!("{:?}", self.op_code)),
+            ("address", address),
+            ("len", self.data_len.to_string()),
+        ];
+        if !self.data.is_empty() {
+            fields.push(("value", self.value()));
         }
+        fields.push(("checksum", checksum));
+        fields
+    }
 
-        if rx_checksum == checksum {
-            Ok(out)
-        } else {
-            eprintln!("rx_checksum: {rx_checksum}");
-            eprintln!("calc_checksum: {checksum}");
-            Err(PacketError::Checksum(rx_checksum, checksum))
+    /// Returns the data as a little-endian integer when it fits one, as hexadecimal bytes otherwise
+    fn value(&self) -> String {
+        let number = match *self.data {
+            [byte] => Some(byte.into()),
+            [low, high] => Some(u16::from_le_bytes([low, high]).into()),
+            [a, b, c, d] => Some(u32::from_le_bytes([a, b, c, d])),
+            _ => None,
+        };
+        if let Some(number) = number {
+            return format!("{number} ({number:#x})");
+        }
+        let mut bytes: Vec<String> = self
+            .data
+            .iter()
+            .take(MAX_SHOWN_BYTES)
+            .map(|byte| format!("{byte:02x}"))
+            .collect();
+        if self.data.len() > MAX_SHOWN_BYTES {
+            bytes.push(format!("... ({} bytes)", self.data.len()));
         }
+        bytes.join(" ")
+    }
+}
+
+/// Returns the name of the register at `address` of a `device`, when this crate knows it
+pub fn register_name(device: DeviceType, address: u32) -> Option<String> {
+    if device == DeviceType::Scanboard
+        && let Ok(address) = ScannerAddress::try_from_primitive(address)
+    {
+        return Some(format!("{address:?}"));
+    }
+    FeatureAddress::try_from_primitive(address)
+        .ok()
+        .map(|address| format!("{address:?}"))
+}
+
+/// Reserves the serial number of a new packet, wrapping around after 255
+fn take_serial() -> u8 {
+    PACKET_SERIAL.fetch_add(1, Ordering::Relaxed)
+}
+
+/// Formats `bytes` as space separated hexadecimal
+pub fn hex(bytes: &[u8]) -> String {
+    bytes
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Number of data bytes shown before the rest of a frame is elided
+const MAX_SHOWN_BYTES: usize = 32;
+
+impl Display for NovastarPacket<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let separator = if f.alternate() { "\n" } else { " " };
+        for (index, (label, value)) in self.fields().iter().enumerate() {
+            if index > 0 {
+                f.write_str(separator)?;
+            }
+            write!(f, "{label}={value}")?;
+        }
+        Ok(())
+    }
+}
+
+impl Debug for NovastarPacket<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut packet = f.debug_struct("NovastarPacket");
+        for (label, value) in self.fields() {
+            packet.field(label, &format_args!("{value}"));
+        }
+        packet.finish()
     }
 }
 
 pub fn build_tx_sender(
     op_code: OpCode,
     dst_addr: u8,
-    address: FeatureAddress,
+    address: impl Into<u32>,
     data: &[u8],
 ) -> Vec<u8> {
-    let my_serial: u8;
-    unsafe {
-        my_serial = PACKET_SERIAL;
-    }
     let out: NovastarPacket = NovastarPacket {
-        direction: 0x55AA,
+        direction: REQUEST,
         ack: 0x00,
-        serial: my_serial,
+        serial: take_serial(),
         src_addr: MY_ADDR,
         dst_addr,
         device_type: DeviceType::Controller,
@@ -132,8 +315,10 @@ pub fn build_tx_sender(
         scanboard_addr: 0x0000,
         op_code,
         reserved2: 0x00,
-        address,
+        address: address.into(),
+        data_len: data.len() as u16,
         data,
+        checksum: 0,
     };
     out.encode()
 }
@@ -142,17 +327,13 @@ pub fn build_tx_sender(
 pub fn build_rx_sender(
     op_code: OpCode,
     dst_addr: u8,
-    address: FeatureAddress,
+    address: impl Into<u32>,
     data: &[u8],
 ) -> Vec<u8> {
-    let my_serial: u8;
-    unsafe {
-        my_serial = PACKET_SERIAL;
-    }
     let out: NovastarPacket = NovastarPacket {
-        direction: 0x55AA,
+        direction: REQUEST,
         ack: 0x00,
-        serial: my_serial,
+        serial: take_serial(),
         src_addr: MY_ADDR,
         dst_addr,
         device_type: DeviceType::Controller,
@@ -160,30 +341,38 @@ pub fn build_rx_sender(
         scanboard_addr: 0x0001,
         op_code,
         reserved2: 0x00,
-        address,
+        address: address.into(),
+        data_len: data.len() as u16,
         data,
+        checksum: 0,
     };
     out.encode()
 }
 
-pub fn build_tx_scanboard(op_code: OpCode, address: FeatureAddress, data: &[u8]) -> Vec<u8> {
-    let my_serial: u8;
-    unsafe {
-        my_serial = PACKET_SERIAL;
-    }
+/// Builds a packet for the receiving card `scanboard_addr` behind output port `port_addr`,
+/// `0xFF` and `0xFFFF` meaning every port and every card
+pub fn build_tx_scanboard_at(
+    op_code: OpCode,
+    port_addr: u8,
+    scanboard_addr: u16,
+    address: impl Into<u32>,
+    data: &[u8],
+) -> Vec<u8> {
     let out: NovastarPacket = NovastarPacket {
-        direction: 0x55AA,
+        direction: REQUEST,
         ack: 0x00,
-        serial: my_serial,
+        serial: take_serial(),
         src_addr: MY_ADDR,
         dst_addr: 0xFF,
         device_type: DeviceType::Scanboard,
-        port_addr: 0xFF,
-        scanboard_addr: 0xFFFF,
+        port_addr,
+        scanboard_addr,
         op_code,
         reserved2: 0x00,
-        address,
+        address: address.into(),
+        data_len: data.len() as u16,
         data,
+        checksum: 0,
     };
     out.encode()
 }
@@ -0,0 +1,106 @@
+/// Role of a controller or of one of its output ports in a redundant setup
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PortRole {
+    /// Drives the receiving cards in normal operation
+    Primary,
+    /// Takes over when its primary fails
+    Backup,
+}
+
+impl From<u8> for PortRole {
+    fn from(value: u8) -> Self {
+        if value == 0 {
+            PortRole::Primary
+        } else {
+            PortRole::Backup
+        }
+    }
+}
+
+impl From<PortRole> for u8 {
+    fn from(role: PortRole) -> Self {
+        match role {
+            PortRole::Primary => 0,
+            PortRole::Backup => 1,
+        }
+    }
+}
+
+/// Two output ports backing each other up, on the same controller
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PortBackupPair {
+    /// Port driving the receiving cards in normal operation
+    pub primary: u8,
+    /// Port taking over when the primary port fails
+    pub backup: u8,
+}
+
+/// Redundancy configuration and state of a [crate::Controller]
+///
+/// The reference table in `types.rs` gives the size of the redundancy registers, not their
+/// content. Their decoding follows the register names and has not been checked against a
+/// published specification: a non-zero byte of `MasterOrSlaveAddr` marks a backup port, and bits
+/// 0 and 1 of `MasterOrSlaveDeviceStateAddr` the role of the controller and an active failover
+#[derive(PartialEq, Eq, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RedundancyState {
+    /// Role of the controller itself
+    pub device_role: PortRole,
+    /// Role of each output port, by port index
+    pub ports: Vec<PortRole>,
+    /// A backup currently carries the output of a failed primary
+    pub failover_active: bool,
+    /// Hot-backup pairs configured between ports of the controller
+    pub backup_pairs: Vec<PortBackupPair>,
+}
+
+impl RedundancyState {
+    /// Returns the indexes of the ports acting as primary
+    pub fn primary_ports(&self) -> impl Iterator<Item = usize> {
+        self.ports_with_role(PortRole::Primary)
+    }
+
+    /// Returns the indexes of the ports acting as backup
+    pub fn backup_ports(&self) -> impl Iterator<Item = usize> {
+        self.ports_with_role(PortRole::Backup)
+    }
+
+    fn ports_with_role(&self, role: PortRole) -> impl Iterator<Item = usize> {
+        self.ports
+            .iter()
+            .enumerate()
+            .filter(move |(_, port)| **port == role)
+            .map(|(index, _)| index)
+    }
+}
+
+/// Decodes the hardware backup information block: a pair count followed by
+/// `primary, backup` port bytes
+///
+/// The reference table only gives the block size, 0x1000 bytes at `Sender_HardWareBackupInfoAddr`.
+/// This layout is assumed rather than documented, and [encode_backup_pairs] writes it back
+pub(crate) fn decode_backup_pairs(data: &[u8]) -> Vec<PortBackupPair> {
+    let count = data.first().copied().unwrap_or(0) as usize;
+    data.get(1..)
+        .unwrap_or_default()
+        .chunks_exact(2)
+        .take(count)
+        .map(|pair| PortBackupPair {
+            primary: pair[0],
+            backup: pair[1],
+        })
+        .collect()
+}
+
+/// Encodes port backup pairs into the hardware backup information block
+pub(crate) fn encode_backup_pairs(pairs: &[PortBackupPair]) -> Vec<u8> {
+    let mut data = Vec::with_capacity(1 + 2 * pairs.len());
+    data.push(pairs.len() as u8);
+    for pair in pairs {
+        data.push(pair.primary);
+        data.push(pair.backup);
+    }
+    data
+}
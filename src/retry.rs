@@ -0,0 +1,50 @@
+use std::time::Duration;
+
+/// How a [crate::Controller] recovers from a broken connexion
+///
+/// When a request fails to be sent or answered, the connexion is reopened from the stored address
+/// or port name, waiting `backoff` before the first attempt and twice as long before each next one
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RetryPolicy {
+    /// Reconnection attempts made after a failed request, `0` never reconnects
+    pub attempts: u32,
+    /// Delay before the first reconnection attempt
+    pub backoff: Duration,
+    /// Longest delay between two reconnection attempts
+    pub max_backoff: Duration,
+    /// Reads are sent again once reconnected
+    pub resend_reads: bool,
+    /// Writes are sent again once reconnected, the controller may then receive them twice
+    pub resend_writes: bool,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            attempts: 3,
+            backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(2),
+            resend_reads: true,
+            resend_writes: false,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Never reconnects, failed requests are returned straight away
+    pub fn none() -> Self {
+        Self {
+            attempts: 0,
+            ..Self::default()
+        }
+    }
+
+    /// Returns the delay before the reconnection attempt number `attempt`, counted from 1
+    pub fn delay(&self, attempt: u32) -> Duration {
+        let factor = 1u32
+            .checked_shl(attempt.saturating_sub(1))
+            .unwrap_or(u32::MAX);
+        self.backoff.saturating_mul(factor).min(self.max_backoff)
+    }
+}
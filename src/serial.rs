@@ -1,7 +1,8 @@
 //! This crate contains the logic to discover and connect to Novastar [Controller]s through serial ports
 
-use num_enum::TryFromPrimitive;
+use num_enum::FromPrimitive;
 use std::{io::Error, time::Duration};
+use tracing::debug;
 
 use crate::{
     controller::Controller,
@@ -42,17 +43,17 @@ pub fn try_com_connect(port_name: &str, baud_rate: u32) -> Result<Option<Control
     let rx_buff: &mut [u8; 22] = &mut [0; 22];
     port.read_exact(rx_buff)?;
 
-    Ok(NovastarPacket::decode(rx_buff).ok().map(|packet| {
-        let dev_id: u16 = u16::from_le_bytes([packet.data[0], packet.data[1]]);
-        let dev_model =
-            SenderCardType::try_from_primitive(dev_id).unwrap_or(SenderCardType::Unknown);
-        if dev_model == SenderCardType::Unknown {
-            println!("Controller returned model ID {dev_id}");
-            println!("{:01$x} ", dev_id, 2);
+    match NovastarPacket::decode(rx_buff) {
+        Ok(packet) => {
+            let dev_id: u16 = u16::from_le_bytes([packet.data[0], packet.data[1]]);
+            Ok(Some(Controller::new(
+                SenderCardType::from_primitive(dev_id),
+                crate::ConnexionType::Serial(port_name.to_string(), port),
+            )))
         }
-        Controller {
-            card_type: dev_model,
-            connexion: crate::ConnexionType::Serial(port_name.to_string(), port),
+        Err(err) => {
+            debug!(port_name, baud_rate, %err, "no controller on serial port");
+            Ok(None)
         }
-    }))
+    }
 }
@@ -0,0 +1,47 @@
+use num_enum::{IntoPrimitive, TryFromPrimitive};
+
+/// Highest field rate the field rate register can hold, in Hz
+pub(crate) const MAX_FIELD_RATE: f32 = u16::MAX as f32 / 100.0;
+
+/// Source of the output field rate
+#[derive(PartialEq, Eq, Clone, Copy, Debug, TryFromPrimitive, IntoPrimitive)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[repr(u8)]
+pub enum FieldRateMode {
+    /// The output follows the field rate of the active input
+    FollowInput = 0x00,
+    /// The output runs at [SyncSettings::field_rate]
+    Custom = 0x01,
+    /// The output is locked to the genlock input
+    Genlock = 0x02,
+}
+
+/// Field rate, genlock and latency settings of a [crate::Controller]
+#[derive(PartialEq, Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SyncSettings {
+    /// Source of the output field rate
+    pub field_rate_mode: FieldRateMode,
+    /// Output field rate in Hz when [FieldRateMode::Custom] is selected, with a 0.01 Hz resolution,
+    /// from 0 to 655.35 Hz
+    pub field_rate: f32,
+    /// A genlock signal is present on the genlock input, ignored when applying settings
+    pub genlock_present: bool,
+    /// The sending and receiving cards skip a frame of buffering
+    pub low_latency: bool,
+}
+
+impl SyncSettings {
+    /// Returns `true` when the output is locked to a present genlock signal
+    pub fn is_genlocked(&self) -> bool {
+        self.field_rate_mode == FieldRateMode::Genlock && self.genlock_present
+    }
+
+    pub(crate) fn decode_field_rate(data: &[u8]) -> f32 {
+        u16::from_le_bytes([data[0], data[1]]) as f32 / 100.0
+    }
+
+    pub(crate) fn encode_field_rate(&self) -> [u8; 2] {
+        ((self.field_rate * 100.0).round() as u16).to_le_bytes()
+    }
+}
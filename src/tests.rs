@@ -1,11 +1,10 @@
-mod types;
-use num_enum::TryFromPrimitive;
-use types::SenderCardType;
+use novastar_core::SenderCardType;
+use num_enum::FromPrimitive;
 
 #[test]
 fn display_sender_card_type() {
     assert_eq!(
-        format!("{}", SenderCardType::try_from_primitive(0x0001).unwrap()),
+        format!("{}", SenderCardType::from_primitive(0x0001)),
         "MCTRL300"
     );
 }
@@ -13,7 +12,1969 @@ fn display_sender_card_type() {
 #[test]
 fn display_renamed_sender_card_type() {
     assert_eq!(
-        format!("{}", SenderCardType::try_from_primitive(0x1101).unwrap()),
+        format!("{}", SenderCardType::from_primitive(0x1101)),
         "MCTRL600/660"
     );
 }
+
+#[test]
+fn unknown_sender_card_type_keeps_model_id() {
+    let model = SenderCardType::from_primitive(0x1234);
+    assert_eq!(model, SenderCardType::Other(0x1234));
+    assert_eq!(u16::from(model), 0x1234);
+    assert_eq!(format!("{model}"), "Unknown (0x1234)");
+    assert_eq!(
+        SenderCardType::from_primitive(0xFFFF),
+        SenderCardType::Unknown
+    );
+}
+
+#[test]
+fn distinct_e8000_display() {
+    assert_eq!(format!("{}", SenderCardType::E8000_1), "E8000 (0x7505)");
+    assert_ne!(
+        format!("{}", SenderCardType::E8000_2),
+        format!("{}", SenderCardType::E8000_3)
+    );
+}
+
+#[test]
+fn parse_sender_card_type() {
+    assert_eq!("MCTRL600/660".parse(), Ok(SenderCardType::MCTRL600_660));
+    assert_eq!("vx1000 pro".parse(), Ok(SenderCardType::VX1000Pro));
+    assert_eq!("VX1000Pro".parse(), Ok(SenderCardType::VX1000Pro));
+    assert_eq!("0x1234".parse(), Ok(SenderCardType::Other(0x1234)));
+    assert_eq!(
+        SenderCardType::from_name("E8000 (0x7647)"),
+        Some(SenderCardType::E8000_2)
+    );
+    assert_eq!("H Series (H20)".parse(), Ok(SenderCardType::H_series_H20));
+    assert_eq!(
+        "planar series (h20)".parse(),
+        Ok(SenderCardType::H20_Planar)
+    );
+    assert_eq!(
+        "MCTRL9000".parse::<SenderCardType>(),
+        Err(novastar_core::UnknownModel("MCTRL9000".to_string()))
+    );
+}
+
+#[test]
+fn unique_sender_card_type_names() {
+    use strum::IntoEnumIterator;
+
+    let names: Vec<String> = SenderCardType::iter()
+        .map(|model| model.to_string())
+        .collect();
+    for (index, name) in names.iter().enumerate() {
+        assert!(!names[index + 1..].contains(name), "{name} is not unique");
+    }
+}
+
+#[test]
+fn config_backup_round_trip() {
+    let backup = novastar_core::ConfigBackup {
+        model_id: 0x1101,
+        serial_number: 0x0123_4567_89AB_CDEF,
+        header: vec![1; 0x100],
+        sender_parameters: vec![2; 0x200],
+        led_parameters: vec![3, 4, 5],
+    };
+    let mut file = Vec::new();
+    backup.write_to(&mut file).unwrap();
+
+    let restored = novastar_core::ConfigBackup::read_from(file.as_slice()).unwrap();
+    assert_eq!(restored, backup);
+    assert_eq!(restored.model().to_string(), "MCTRL600/660");
+}
+
+#[test]
+fn config_backup_rejects_foreign_file() {
+    assert!(matches!(
+        novastar_core::ConfigBackup::read_from(&b"not a backup file"[..]),
+        Err(novastar_core::BackupError::Magic)
+    ));
+}
+
+#[test]
+fn config_backup_rejects_truncated_block() {
+    let mut file = b"NSCB\x01".to_vec();
+    file.extend_from_slice(&[0; 10]);
+    file.extend_from_slice(&u32::MAX.to_le_bytes());
+    file.extend_from_slice(&[1; 16]);
+    assert!(matches!(
+        novastar_core::ConfigBackup::read_from(file.as_slice()),
+        Err(novastar_core::BackupError::Io(err)) if err.kind() == std::io::ErrorKind::UnexpectedEof
+    ));
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn controller_descriptor_serde_round_trip() {
+    use novastar_core::{ControllerDescriptor, Transport};
+
+    let json = serde_json::json!({
+        "transport": { "Serial": { "port_name": "/dev/ttyUSB0", "baud_rate": 115200 } },
+        "model": { "Other": 0x1234 },
+    });
+    let descriptor: ControllerDescriptor = serde_json::from_value(json.clone()).unwrap();
+    assert_eq!(
+        descriptor.transport,
+        Transport::Serial {
+            port_name: "/dev/ttyUSB0".to_string(),
+            baud_rate: 115200,
+        }
+    );
+    assert_eq!(u16::from(descriptor.model), 0x1234);
+    assert_eq!(serde_json::to_value(&descriptor).unwrap(), json);
+
+    let settings = novastar_core::SyncSettings {
+        field_rate_mode: novastar_core::FieldRateMode::Genlock,
+        field_rate: 50.0,
+        genlock_present: true,
+        low_latency: true,
+    };
+    let json = serde_json::to_value(settings).unwrap();
+    assert_eq!(json["field_rate_mode"], "Genlock");
+    assert_eq!(
+        serde_json::from_value::<novastar_core::SyncSettings>(json).unwrap(),
+        settings
+    );
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn abl_settings_serde_fields() {
+    let settings = novastar_core::AblSettings {
+        enabled: true,
+        energy_saving: false,
+        threshold: 80,
+        min_level: 20,
+    };
+    let json = serde_json::to_value(settings).unwrap();
+    assert_eq!(
+        json,
+        serde_json::json!({
+            "enabled": true,
+            "energy_saving": false,
+            "threshold": 80,
+            "min_level": 20,
+        })
+    );
+    assert_eq!(
+        serde_json::from_value::<novastar_core::AblSettings>(json).unwrap(),
+        settings
+    );
+}
+
+#[test]
+fn model_capabilities() {
+    use novastar_core::{Feature, PortAddressFamily, ProtocolFamily};
+
+    let mctrl300 = SenderCardType::MCTRL300.capabilities();
+    assert_eq!(mctrl300.ethernet_ports, Some(2));
+    assert!(mctrl300.supports(Feature::Backup));
+    assert!(mctrl300.supports(Feature::RegisterAccess));
+    assert!(!mctrl300.supports(Feature::Hdr));
+    assert_eq!(
+        mctrl300.port_address_family(),
+        Some(PortAddressFamily::Legacy)
+    );
+
+    let vx2000 = SenderCardType::from_primitive(0x622C).capabilities();
+    assert_eq!(vx2000.port_address_family(), Some(PortAddressFamily::New32));
+
+    let h9 = SenderCardType::H_series_H9.capabilities();
+    assert_eq!(h9.protocol, ProtocolFamily::HSeriesHttp);
+    assert!(!h9.supports(Feature::Hdr));
+    assert!(!h9.supports(Feature::RegisterAccess));
+
+    let unknown = SenderCardType::from_primitive(0x1234).capabilities();
+    assert_eq!(unknown.ethernet_ports, None);
+    assert_eq!(unknown.port_address_family(), None);
+    assert!(unknown.supports(Feature::RegisterAccess));
+    assert!(!unknown.supports(Feature::Hdr));
+}
+
+/// Builds a binary protocol frame with a valid checksum
+fn novastar_frame(
+    direction: u16,
+    op_code: u8,
+    address: u32,
+    data_len: u16,
+    data: &[u8],
+) -> Vec<u8> {
+    let mut frame = direction.to_be_bytes().to_vec();
+    frame.extend_from_slice(&[
+        0x00, 0x07, 0xFE, 0x00, 0x00, 0x00, 0x00, 0x00, op_code, 0x00,
+    ]);
+    frame.extend_from_slice(&address.to_le_bytes());
+    frame.extend_from_slice(&data_len.to_le_bytes());
+    frame.extend_from_slice(data);
+    let checksum = frame[2..]
+        .iter()
+        .fold(0x5555u16, |sum, byte| sum.wrapping_add(*byte as u16));
+    frame.extend_from_slice(&checksum.to_le_bytes());
+    frame
+}
+
+/// Builds an Ethernet frame carrying an IPv4 packet from `source` to `destination`
+fn ethernet_ipv4(protocol: u8, source: [u8; 4], destination: [u8; 4], payload: &[u8]) -> Vec<u8> {
+    let mut packet = vec![0; 12];
+    packet.extend_from_slice(&[0x08, 0x00, 0x45, 0x00]);
+    packet.extend_from_slice(&(20 + payload.len() as u16).to_be_bytes());
+    packet.extend_from_slice(&[0, 0, 0, 0, 64, protocol, 0, 0]);
+    packet.extend_from_slice(&source);
+    packet.extend_from_slice(&destination);
+    packet.extend_from_slice(payload);
+    // Ethernet padding, which must not be read as TCP data
+    packet.extend_from_slice(&[0; 4]);
+    packet
+}
+
+fn tcp_segment(
+    source_port: u16,
+    destination_port: u16,
+    seq: u32,
+    syn: bool,
+    data: &[u8],
+) -> Vec<u8> {
+    let mut segment = source_port.to_be_bytes().to_vec();
+    segment.extend_from_slice(&destination_port.to_be_bytes());
+    segment.extend_from_slice(&seq.to_be_bytes());
+    segment.extend_from_slice(&[
+        0,
+        0,
+        0,
+        0,
+        0x50,
+        if syn { 0x02 } else { 0x18 },
+        0xFF,
+        0xFF,
+        0,
+        0,
+        0,
+        0,
+    ]);
+    segment.extend_from_slice(data);
+    segment
+}
+
+/// Returns the link layer packets of a brightness read, with the response split in two
+/// segments received out of order, and a discovery request
+fn captured_packets() -> Vec<Vec<u8>> {
+    let host = [10, 0, 0, 1];
+    let controller = [10, 0, 0, 2];
+    let request = novastar_frame(0x55AA, 0, 0x0200_0001, 1, &[]);
+    let response = novastar_frame(0xAA55, 0, 0x0200_0001, 1, &[0x80]);
+    let mut discovery = 3800u16.to_be_bytes().to_vec();
+    discovery.extend_from_slice(&3800u16.to_be_bytes());
+    discovery.extend_from_slice(&[0, 16, 0, 0]);
+    discovery.extend_from_slice(b"rqProMi:");
+    vec![
+        ethernet_ipv4(17, host, [255, 255, 255, 255], &discovery),
+        ethernet_ipv4(
+            6,
+            host,
+            controller,
+            &tcp_segment(50000, 5200, 1000, true, &[]),
+        ),
+        ethernet_ipv4(
+            6,
+            controller,
+            host,
+            &tcp_segment(5200, 50000, 7000, true, &[]),
+        ),
+        ethernet_ipv4(
+            6,
+            host,
+            controller,
+            &tcp_segment(50000, 5200, 1001, false, &request),
+        ),
+        ethernet_ipv4(
+            6,
+            controller,
+            host,
+            &tcp_segment(5200, 50000, 7011, false, &response[10..]),
+        ),
+        ethernet_ipv4(
+            6,
+            controller,
+            host,
+            &tcp_segment(5200, 50000, 7001, false, &response[..10]),
+        ),
+    ]
+}
+
+fn check_captured_frames(frames: &[novastar_core::capture::CapturedFrame]) {
+    use novastar_core::capture::Protocol;
+
+    assert_eq!(frames.len(), 3);
+    assert_eq!(frames[0].protocol, Protocol::Udp);
+    assert_eq!(frames[0].bytes, b"rqProMi:");
+
+    let request = frames[1].packet().unwrap();
+    assert_eq!(request.direction, novastar_core::REQUEST);
+    assert_eq!(
+        request.address_name().as_deref(),
+        Some("GlobalBrightnessAddr")
+    );
+    assert_eq!(request.data_len, 1);
+    assert!(request.data.is_empty());
+
+    let response = frames[2].packet().unwrap();
+    assert_eq!(frames[2].source, "10.0.0.2:5200".parse().unwrap());
+    assert_eq!(response.data, [0x80]);
+    assert_eq!(
+        frames[2].timestamp,
+        std::time::Duration::from_micros(1_700_000_000_000_005)
+    );
+}
+
+/// Builds a little endian pcap capture of Ethernet `packets`
+fn pcap_capture(packets: &[Vec<u8>]) -> Vec<u8> {
+    let mut capture = vec![0xD4, 0xC3, 0xB2, 0xA1, 2, 0, 4, 0];
+    capture.extend_from_slice(&[0; 8]);
+    capture.extend_from_slice(&65535u32.to_le_bytes());
+    capture.extend_from_slice(&1u32.to_le_bytes());
+    for (index, packet) in packets.iter().enumerate() {
+        capture.extend_from_slice(&1_700_000_000u32.to_le_bytes());
+        capture.extend_from_slice(&(index as u32).to_le_bytes());
+        capture.extend_from_slice(&(packet.len() as u32).to_le_bytes());
+        capture.extend_from_slice(&(packet.len() as u32).to_le_bytes());
+        capture.extend_from_slice(packet);
+    }
+    capture
+}
+
+/// Builds a little endian pcapng block
+fn pcapng_block(block_type: u32, body: &[u8]) -> Vec<u8> {
+    let len = 12 + body.len().div_ceil(4) * 4;
+    let mut block = block_type.to_le_bytes().to_vec();
+    block.extend_from_slice(&(len as u32).to_le_bytes());
+    block.extend_from_slice(body);
+    block.resize(len - 4, 0);
+    block.extend_from_slice(&(len as u32).to_le_bytes());
+    block
+}
+
+/// Builds a little endian pcapng section with one Ethernet interface carrying `options`
+fn pcapng_capture(options: &[u8]) -> Vec<u8> {
+    let mut section = 0x1A2B_3C4Du32.to_le_bytes().to_vec();
+    section.extend_from_slice(&[1, 0, 0, 0]);
+    section.extend_from_slice(&u64::MAX.to_le_bytes());
+    let mut interface = vec![1, 0, 0, 0, 0, 0, 0, 0];
+    interface.extend_from_slice(options);
+
+    let mut capture = pcapng_block(0x0A0D_0D0A, &section);
+    capture.extend(pcapng_block(1, &interface));
+    capture
+}
+
+#[test]
+fn read_pcap_capture() {
+    let capture = pcap_capture(&captured_packets());
+    check_captured_frames(&novastar_core::capture::parse_capture(&capture).unwrap());
+}
+
+#[test]
+fn read_pcapng_capture() {
+    // Nanosecond timestamps
+    let mut capture = pcapng_capture(&[9, 0, 1, 0, 9, 0, 0, 0, 0, 0, 0, 0]);
+    for (index, packet) in captured_packets().iter().enumerate() {
+        let ticks = 1_700_000_000_000_000_000u64 + index as u64 * 1000;
+        let mut body = 0u32.to_le_bytes().to_vec();
+        body.extend_from_slice(&((ticks >> 32) as u32).to_le_bytes());
+        body.extend_from_slice(&(ticks as u32).to_le_bytes());
+        body.extend_from_slice(&(packet.len() as u32).to_le_bytes());
+        body.extend_from_slice(&(packet.len() as u32).to_le_bytes());
+        body.extend_from_slice(packet);
+        capture.extend(pcapng_block(6, &body));
+    }
+    check_captured_frames(&novastar_core::capture::parse_capture(&capture).unwrap());
+}
+
+#[test]
+fn dissect_packet() {
+    use novastar_core::NovastarPacket;
+
+    let frame = novastar_frame(0xAA55, 1, 0x0200_0001, 1, &[0x80]);
+    let packet = NovastarPacket::decode(&frame).unwrap();
+    assert_eq!(
+        packet.to_string(),
+        "direction=response ack=0x00 serial=7 src=0xfe dst=0x00 device=Controller port=0x00 \
+         scanboard=0x0000 op=Write address=GlobalBrightnessAddr (0x02000001) len=1 \
+         value=128 (0x80) checksum=0x56df ok"
+    );
+    assert_eq!(format!("{packet:#}").lines().count(), 13);
+    assert!(format!("{packet:?}").contains("address: GlobalBrightnessAddr (0x02000001)"));
+
+    let mut corrupted = frame.clone();
+    corrupted[18] = 0x81;
+    assert!(NovastarPacket::decode(&corrupted).is_err());
+    let packet = NovastarPacket::parse(&corrupted).unwrap();
+    assert!(!packet.checksum_ok());
+    assert!(
+        packet
+            .to_string()
+            .ends_with("checksum=0x56df bad, expected 0x56e0")
+    );
+}
+
+#[test]
+fn parse_scanboard_address() {
+    let mut frame = novastar_frame(0x55AA, 1, 0x0200_0001, 1, &[0x80]);
+    frame[8..10].copy_from_slice(&[0x02, 0x01]);
+    let packet = novastar_core::NovastarPacket::parse(&frame).unwrap();
+    assert_eq!(packet.scanboard_addr, 0x0102);
+    assert!(packet.to_string().contains("scanboard=0x0102"));
+}
+
+#[test]
+fn register_names_depend_on_device() {
+    let mut frame = novastar_frame(0x55AA, 1, 0x0200_0002, 3, &[1, 2, 3]);
+    let packet = novastar_core::NovastarPacket::parse(&frame).unwrap();
+    assert_eq!(packet.address_name(), None);
+    frame[6] = 1;
+    let packet = novastar_core::NovastarPacket::parse(&frame).unwrap();
+    assert_eq!(packet.address_name().as_deref(), Some("RedBrightnessAddr"));
+
+    // The display mode only exists on the receiving cards
+    let mut frame = novastar_frame(0x55AA, 1, 0x0100_0000, 1, &[2]);
+    let packet = novastar_core::NovastarPacket::parse(&frame).unwrap();
+    assert_eq!(packet.address_name(), None);
+    frame[6] = 1;
+    let packet = novastar_core::NovastarPacket::parse(&frame).unwrap();
+    assert_eq!(packet.address_name().as_deref(), Some("DisplayModeAddr"));
+}
+
+#[test]
+fn reject_unknown_capture_format() {
+    assert!(matches!(
+        novastar_core::capture::parse_capture(b"not a capture file"),
+        Err(novastar_core::capture::CaptureError::Format)
+    ));
+}
+
+#[test]
+fn reject_malformed_timestamp_resolution() {
+    use novastar_core::capture::{CaptureError, parse_capture};
+
+    // if_tsresol option without its value
+    assert!(matches!(
+        parse_capture(&pcapng_capture(&[9, 0, 1, 0])),
+        Err(CaptureError::Truncated)
+    ));
+    // 10^100 and 2^100 ticks per second
+    assert!(matches!(
+        parse_capture(&pcapng_capture(&[9, 0, 1, 0, 100, 0, 0, 0])),
+        Err(CaptureError::Format)
+    ));
+    assert!(matches!(
+        parse_capture(&pcapng_capture(&[9, 0, 1, 0, 0x80 | 100, 0, 0, 0])),
+        Err(CaptureError::Format)
+    ));
+}
+
+#[test]
+fn skip_malformed_ipv4_headers() {
+    // Discovery datagrams whose header lengths exceed the packet, or are below 20 bytes
+    let packets: Vec<Vec<u8>> = [0x4F, 0x41]
+        .into_iter()
+        .map(|version_and_len| {
+            let mut packet = captured_packets().remove(0);
+            packet[14] = version_and_len;
+            packet
+        })
+        .collect();
+    let frames = novastar_core::capture::parse_capture(&pcap_capture(&packets)).unwrap();
+    assert!(frames.is_empty());
+}
+
+/// Answer of [controller_stub] to a read request
+enum Reply {
+    /// Answers with this data
+    Data(Vec<u8>),
+    /// Waits before dropping the connection
+    Hang(std::time::Duration),
+}
+
+/// Request received by [controller_stub]
+#[derive(PartialEq, Debug)]
+struct Request {
+    op: u8,
+    device: u8,
+    port: u8,
+    card: u16,
+    address: u32,
+    /// Data of a write, empty for a read
+    data: Vec<u8>,
+}
+
+/// Accepts one connection per session and answers its handshake with the model ID `model`
+///
+/// Each read is then answered with the next reply of the session, and the connection is dropped
+/// on a read without any reply left. Writes to the sending card are acknowledged, writes to the
+/// receiving cards are not. Returns the requests received after the handshakes
+fn controller_stub(
+    model: u16,
+    sessions: Vec<Vec<Reply>>,
+) -> (std::net::SocketAddr, std::thread::JoinHandle<Vec<Request>>) {
+    use std::io::{Read, Write};
+
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let handle = std::thread::spawn(move || {
+        let mut requests = Vec::new();
+        for replies in sessions {
+            let (mut stream, _) = listener.accept().unwrap();
+            stream.read_exact(&mut [0; 20]).unwrap();
+            stream
+                .write_all(&novastar_frame(
+                    0xAA55,
+                    0,
+                    0x0000_0002,
+                    2,
+                    &model.to_le_bytes(),
+                ))
+                .unwrap();
+
+            let mut replies = replies.into_iter();
+            let mut header = [0; 18];
+            while stream.read_exact(&mut header).is_ok() {
+                let op = header[10];
+                let address = u32::from_le_bytes(header[12..16].try_into().unwrap());
+                let len = u16::from_le_bytes([header[16], header[17]]) as usize;
+                // Read requests only carry their checksum after the header
+                let mut data = vec![0; if op == 1 { len + 2 } else { 2 }];
+                stream.read_exact(&mut data).unwrap();
+                data.truncate(data.len() - 2);
+                requests.push(Request {
+                    op,
+                    device: header[6],
+                    port: header[7],
+                    card: u16::from_le_bytes([header[8], header[9]]),
+                    address,
+                    data,
+                });
+
+                if op == 1 {
+                    if header[6] == 0 {
+                        stream
+                            .write_all(&novastar_frame(0xAA55, 1, address, 0, &[]))
+                            .unwrap();
+                    }
+                    continue;
+                }
+                match replies.next() {
+                    Some(Reply::Data(data)) => stream
+                        .write_all(&novastar_frame(
+                            0xAA55,
+                            0,
+                            address,
+                            data.len() as u16,
+                            &data,
+                        ))
+                        .unwrap(),
+                    Some(Reply::Hang(delay)) => {
+                        std::thread::sleep(delay);
+                        break;
+                    }
+                    None => break,
+                }
+            }
+        }
+        requests
+    });
+    (addr, handle)
+}
+
+/// Returns the address and data of the writes among `requests`
+fn writes(requests: &[Request]) -> Vec<(u32, Vec<u8>)> {
+    requests
+        .iter()
+        .filter(|request| request.op == 1)
+        .map(|request| (request.address, request.data.clone()))
+        .collect()
+}
+
+/// Returns the address of the reads among `requests`
+fn reads(requests: &[Request]) -> Vec<u32> {
+    requests
+        .iter()
+        .filter(|request| request.op == 0)
+        .map(|request| request.address)
+        .collect()
+}
+
+#[test]
+fn reconnect_and_resend_read() {
+    let (addr, stub) = controller_stub(0x0001, vec![vec![], vec![Reply::Data(vec![0x80])]]);
+    let mut controller = novastar_core::Controller::try_from_tcp_addr(addr).unwrap();
+    controller.set_retry_policy(novastar_core::RetryPolicy {
+        backoff: std::time::Duration::from_millis(1),
+        ..Default::default()
+    });
+    assert_eq!(controller.brightness().unwrap(), 0x80);
+    drop(controller);
+    let requests = stub.join().unwrap();
+    assert_eq!(requests.len(), 2);
+    assert!(
+        requests
+            .iter()
+            .all(|request| request.address == 0x0200_0001)
+    );
+}
+
+#[test]
+fn read_fails_without_retry() {
+    let (addr, stub) = controller_stub(0x0001, vec![vec![]]);
+    let mut controller = novastar_core::Controller::try_from_tcp_addr(addr).unwrap();
+    controller.set_retry_policy(novastar_core::RetryPolicy::none());
+    let err = controller.brightness().unwrap_err();
+    assert!(matches!(err, novastar_core::Error::Read(_)));
+    assert!(err.is_connection_error());
+    assert_eq!(stub.join().unwrap().len(), 1);
+}
+
+#[test]
+fn h_series_refuses_binary_requests() {
+    use novastar_core::{Confirmation, Error, Feature, Target, TestPattern};
+
+    let (addr, stub) = controller_stub(0x7b46, vec![vec![]]);
+    let mut controller = novastar_core::Controller::try_from_tcp_addr(addr).unwrap();
+    let unsupported = |result: Result<(), Error>| {
+        assert!(matches!(
+            result,
+            Err(Error::Unsupported(Feature::RegisterAccess))
+        ))
+    };
+    unsupported(controller.blackout());
+    unsupported(controller.show_test_pattern(TestPattern::Red, Target::Screen));
+    unsupported(controller.save_to_hardware());
+    unsupported(controller.factory_reset(Confirmation::discard_settings()));
+    unsupported(controller.set_screen_peak_luminance(1000));
+    unsupported(controller.sync_clock());
+    // With the web API, the brightness is read from the processor instead
+    #[cfg(not(feature = "h-series"))]
+    unsupported(controller.brightness().map(|_| ()));
+    drop(controller);
+    assert!(stub.join().unwrap().is_empty());
+}
+
+#[test]
+fn backup_and_restore() {
+    // Receiving card parameters spanning three requests
+    let led_parameters: Vec<u8> = (0..0x10010).map(|i| i as u8).collect();
+    let (addr, stub) = controller_stub(
+        0x0001,
+        vec![vec![
+            Reply::Data(vec![1]),
+            Reply::Data(vec![2; 0x100]),
+            Reply::Data(vec![3; 0x200]),
+            Reply::Data((led_parameters.len() as u32).to_le_bytes().to_vec()),
+            Reply::Data(led_parameters[..0x8000].to_vec()),
+            Reply::Data(led_parameters[0x8000..0x10000].to_vec()),
+            Reply::Data(led_parameters[0x10000..].to_vec()),
+            Reply::Data(0x0123_4567_89AB_CDEFu64.to_le_bytes().to_vec()),
+            Reply::Data(vec![1]),
+        ]],
+    );
+    let mut controller = novastar_core::Controller::try_from_tcp_addr(addr).unwrap();
+    let backup = controller.backup().unwrap();
+    assert_eq!(backup.model_id, 0x0001);
+    assert_eq!(backup.serial_number, 0x0123_4567_89AB_CDEF);
+    assert_eq!(backup.header, vec![2; 0x100]);
+    assert_eq!(backup.sender_parameters, vec![3; 0x200]);
+    assert_eq!(backup.led_parameters, led_parameters);
+    controller.restore(&backup).unwrap();
+    drop(controller);
+
+    let requests = stub.join().unwrap();
+    assert_eq!(
+        reads(&requests),
+        [
+            0x1A00_0001,
+            0x1A00_0100,
+            0x1A00_0101,
+            0x1A00_0200,
+            0x1A00_0204,
+            0x1A00_8204,
+            0x1A01_0204,
+            0x0000_0016,
+            0x1A00_0002,
+        ]
+    );
+    assert_eq!(
+        writes(&requests),
+        [
+            (0x1A00_0001, vec![0]),
+            (0x1A00_0000, vec![0]),
+            (0x1A00_0100, vec![2; 0x100]),
+            (0x1A00_0101, vec![3; 0x200]),
+            (0x1A00_0200, 0x10010u32.to_le_bytes().to_vec()),
+            (0x1A00_0204, led_parameters[..0x8000].to_vec()),
+            (0x1A00_8204, led_parameters[0x8000..0x10000].to_vec()),
+            (0x1A01_0204, led_parameters[0x10000..].to_vec()),
+            (0x1A00_0002, vec![0]),
+            (0x1A00_0000, vec![1]),
+        ]
+    );
+}
+
+/// Runs `operations` on a controller connected to a [controller_stub] answering reads with
+/// `replies`, and returns the requests the stub received
+fn record(
+    model: u16,
+    replies: Vec<Reply>,
+    operations: impl FnOnce(&mut novastar_core::Controller),
+) -> Vec<Request> {
+    let (addr, stub) = controller_stub(model, vec![replies]);
+    let mut controller = novastar_core::Controller::try_from_tcp_addr(addr).unwrap();
+    operations(&mut controller);
+    drop(controller);
+    stub.join().unwrap()
+}
+
+#[test]
+fn display_state_on_the_wire() {
+    use novastar_core::DisplayState;
+
+    let requests = record(
+        0x0001,
+        vec![Reply::Data(vec![0]), Reply::Data(vec![1, 0, 0, 0])],
+        |controller| {
+            controller.blackout().unwrap();
+            controller.freeze().unwrap();
+            controller.normal().unwrap();
+            assert_eq!(controller.display_state().unwrap(), DisplayState::Blackout);
+        },
+    );
+    // The display mode goes to every receiving card, the flags to the sending card
+    assert_eq!(
+        requests[0],
+        Request {
+            op: 1,
+            device: 1,
+            port: 0xFF,
+            card: 0xFFFF,
+            address: 0x0100_0000,
+            data: vec![2],
+        }
+    );
+    assert!(requests[1..3].iter().all(|request| request.device == 0));
+    assert_eq!(
+        writes(&requests),
+        [
+            (0x0100_0000, vec![2]),
+            (0x0220_0140, vec![1, 0, 0, 0]),
+            (0x0200_00EE, vec![0]),
+            (0x0100_0000, vec![1]),
+            (0x0220_0140, vec![0, 0, 0, 0]),
+            (0x0200_00EE, vec![0]),
+            (0x0100_0000, vec![0]),
+            (0x0220_0140, vec![0, 0, 0, 0]),
+            (0x0200_00EE, vec![1]),
+        ]
+    );
+    assert_eq!(reads(&requests), [0x0200_00EE, 0x0220_0140]);
+}
+
+#[test]
+fn test_patterns_on_the_wire() {
+    use novastar_core::{Target, TestPattern};
+
+    let requests = record(0x0001, vec![], |controller| {
+        controller
+            .show_test_pattern(TestPattern::Grid, Target::Screen)
+            .unwrap();
+        controller
+            .show_test_pattern(TestPattern::Red, Target::Port(2))
+            .unwrap();
+        controller
+            .show_test_pattern(
+                TestPattern::White,
+                Target::Card {
+                    port: 1,
+                    card: 0x0102,
+                },
+            )
+            .unwrap();
+        controller.clear_test_pattern().unwrap();
+    });
+    let test_point = |port, card, pattern| Request {
+        op: 1,
+        device: 1,
+        port,
+        card,
+        address: 0x0100_0003,
+        data: vec![pattern],
+    };
+    assert_eq!(
+        requests,
+        [
+            test_point(0xFF, 0xFFFF, 0x0A),
+            test_point(2, 0xFFFF, 0x02),
+            test_point(1, 0x0102, 0x05),
+            test_point(0xFF, 0xFFFF, 0x00),
+        ]
+    );
+}
+
+#[test]
+fn save_reload_and_reset_on_the_wire() {
+    use novastar_core::Confirmation;
+
+    let requests = record(0x0001, vec![], |controller| {
+        controller.save_to_hardware().unwrap();
+        controller
+            .reload_from_flash(Confirmation::discard_settings())
+            .unwrap();
+        controller
+            .factory_reset(Confirmation::discard_settings())
+            .unwrap();
+    });
+    let devices: Vec<u8> = requests.iter().map(|request| request.device).collect();
+    assert_eq!(devices, [0, 1, 1, 0]);
+    assert_eq!(
+        writes(&requests),
+        [
+            (0x0100_0001, vec![1]),
+            (0x0100_0011, vec![1]),
+            (0x0100_0010, vec![1]),
+            (0x0100_0002, vec![1]),
+        ]
+    );
+}
+
+#[test]
+fn redundancy_on_the_wire() {
+    use novastar_core::{Error, PortBackupPair, PortRole};
+
+    let mut backup_info = vec![2, 0, 1, 2, 3];
+    backup_info.resize(0x1000, 0);
+    let requests = record(
+        0x1101,
+        vec![
+            Reply::Data(vec![0, 1, 0, 1]),
+            Reply::Data(vec![0x03]),
+            Reply::Data(backup_info),
+        ],
+        |controller| {
+            let state = controller.redundancy_state().unwrap();
+            assert_eq!(state.device_role, PortRole::Backup);
+            assert!(state.failover_active);
+            assert_eq!(state.primary_ports().collect::<Vec<_>>(), [0, 2]);
+            assert_eq!(state.backup_ports().collect::<Vec<_>>(), [1, 3]);
+            assert_eq!(
+                state.backup_pairs,
+                [
+                    PortBackupPair {
+                        primary: 0,
+                        backup: 1
+                    },
+                    PortBackupPair {
+                        primary: 2,
+                        backup: 3
+                    },
+                ]
+            );
+
+            let pair = PortBackupPair {
+                primary: 0,
+                backup: 1,
+            };
+            controller.set_port_backup_pairs(&[pair]).unwrap();
+            controller.set_port_backup_pairs(&[]).unwrap();
+            let beyond = PortBackupPair {
+                primary: 0,
+                backup: 4,
+            };
+            assert!(matches!(
+                controller.set_port_backup_pairs(&[beyond]),
+                Err(Error::InvalidPort(4))
+            ));
+            let pair_with = |primary, backup| PortBackupPair { primary, backup };
+            assert!(matches!(
+                controller.set_port_backup_pairs(&[pair_with(2, 2)]),
+                Err(Error::InvalidPort(2))
+            ));
+            assert!(matches!(
+                controller.set_port_backup_pairs(&[pair_with(0, 1), pair_with(1, 2)]),
+                Err(Error::InvalidPort(1))
+            ));
+            assert!(matches!(
+                controller.set_port_backup_pairs(&[
+                    pair_with(0, 1),
+                    pair_with(2, 3),
+                    pair_with(0, 2)
+                ]),
+                Err(Error::OutOfRange { value: 3, max: 2 })
+            ));
+        },
+    );
+    assert_eq!(reads(&requests), [0x0200_0018, 0x0200_009D, 0x0230_0000]);
+    assert_eq!(
+        writes(&requests),
+        [(0x0230_0000, vec![1, 0, 1]), (0x0230_0000, vec![0])]
+    );
+}
+
+#[test]
+fn video_processing_on_the_wire() {
+    use novastar_core::{Error, Feature, Mosaic, Rect, ScalingMode};
+
+    let crop = Rect {
+        x: 0x0102,
+        y: 0x0304,
+        width: 1920,
+        height: 1080,
+    };
+    let window = Rect {
+        x: 0,
+        y: 0,
+        width: 3840,
+        height: 2160,
+    };
+    let mosaic = Mosaic {
+        enabled: true,
+        area: crop,
+        block_width: 16,
+        block_height: 32,
+    };
+    let mut mosaic_bytes = vec![1, 0x02, 0x01, 0x04, 0x03, 0x80, 0x07, 0x38, 0x04];
+    mosaic_bytes.extend_from_slice(&[16, 0, 32, 0]);
+    let requests = record(
+        0x612A,
+        vec![
+            Reply::Data(vec![0x01]),
+            Reply::Data(vec![0x10, 0x00, 0x20, 0x00]),
+            Reply::Data(vec![0x02, 0x01, 0x04, 0x03, 0x80, 0x07, 0x38, 0x04]),
+            Reply::Data(vec![0, 0, 0, 0, 0x00, 0x0F, 0x70, 0x08]),
+            Reply::Data(mosaic_bytes.clone()),
+        ],
+        |controller| {
+            let video = controller.video_processing().unwrap();
+            assert_eq!(video.scaling_mode, ScalingMode::FullScreen);
+            assert_eq!(video.screen_offset, (0x10, 0x20));
+            assert_eq!(video.input_crop, crop);
+            assert_eq!(video.output_window, window);
+            assert_eq!(video.mosaic, mosaic);
+
+            controller.set_scaling_mode(ScalingMode::Custom).unwrap();
+            controller.set_screen_offset(0x0102, 0x0304).unwrap();
+            controller.set_input_crop(crop).unwrap();
+            controller.set_output_window(window).unwrap();
+            controller.set_mosaic(mosaic).unwrap();
+        },
+    );
+    assert_eq!(
+        reads(&requests),
+        [
+            0x0220_0000,
+            0x0220_0001,
+            0x0220_0005,
+            0x0220_000D,
+            0x0220_0015
+        ]
+    );
+    assert_eq!(
+        writes(&requests),
+        [
+            (0x0220_0000, vec![0x02]),
+            (0x0220_0001, vec![0x02, 0x01, 0x04, 0x03]),
+            (
+                0x0220_0005,
+                vec![0x02, 0x01, 0x04, 0x03, 0x80, 0x07, 0x38, 0x04]
+            ),
+            (0x0220_000D, vec![0, 0, 0, 0, 0x00, 0x0F, 0x70, 0x08]),
+            (0x0220_0015, mosaic_bytes),
+        ]
+    );
+
+    // Sending cards without video processing are refused before anything is sent
+    let requests = record(0x0001, vec![], |controller| {
+        assert!(matches!(
+            controller.set_scaling_mode(ScalingMode::Custom),
+            Err(Error::Unsupported(Feature::VideoProcessing))
+        ));
+    });
+    assert!(requests.is_empty());
+}
+
+#[test]
+fn hdr_on_the_wire() {
+    use novastar_core::{Error, HdrSettings};
+
+    let settings = HdrSettings {
+        enabled: true,
+        screen_peak_luminance: 1000,
+        ambient_light: 5,
+        hdr10_min_luminance: 2,
+        hlg_model: 3,
+    };
+    let requests = record(
+        0x1103,
+        vec![
+            Reply::Data(vec![1, 0xE8, 0x03, 5]),
+            Reply::Data(vec![2]),
+            Reply::Data(vec![3]),
+            Reply::Data(3000u16.to_le_bytes().to_vec()),
+            Reply::Data(3000u16.to_le_bytes().to_vec()),
+        ],
+        |controller| {
+            assert_eq!(controller.hdr_settings().unwrap(), settings);
+            controller.set_hdr_settings(&settings).unwrap();
+            let too_bright = HdrSettings {
+                screen_peak_luminance: 4000,
+                ..settings
+            };
+            assert!(matches!(
+                controller.set_hdr_settings(&too_bright),
+                Err(Error::OutOfRange {
+                    value: 4000,
+                    max: 3000
+                })
+            ));
+        },
+    );
+    assert_eq!(
+        reads(&requests),
+        [
+            0x0220_0117,
+            0x0220_012E,
+            0x0220_0129,
+            0x0283_0006,
+            0x0283_0006
+        ]
+    );
+    assert_eq!(
+        writes(&requests),
+        [
+            (0x0220_0117, vec![1, 0xE8, 0x03, 5]),
+            (0x0220_012E, vec![2]),
+            (0x0220_0129, vec![3]),
+        ]
+    );
+
+    let requests = record(0x0001, vec![], |controller| {
+        assert!(controller.hdr_settings().is_err());
+    });
+    assert!(requests.is_empty());
+}
+
+#[test]
+fn abl_on_the_wire() {
+    use novastar_core::Error;
+
+    let block: Vec<u8> = [80, 20, 0xAA, 0xBB, 1, 1, 0xE8, 0x03]
+        .into_iter()
+        .chain(0xC0..0xC8)
+        .collect();
+    let requests = record(
+        0x1101,
+        vec![Reply::Data(block.clone()), Reply::Data(block.clone())],
+        |controller| {
+            let mut settings = controller.abl_settings().unwrap();
+            assert!(settings.enabled);
+            assert!(settings.energy_saving);
+            assert_eq!(settings.threshold, 80);
+            assert_eq!(settings.min_level, 20);
+
+            settings.threshold = 50;
+            settings.enabled = false;
+            controller.set_abl_settings(&settings).unwrap();
+            settings.min_level = 101;
+            assert!(matches!(
+                controller.set_abl_settings(&settings),
+                Err(Error::OutOfRange {
+                    value: 101,
+                    max: 100
+                })
+            ));
+            controller.set_abl_enabled(true).unwrap();
+            controller.set_energy_saving(false).unwrap();
+        },
+    );
+    // Bytes which are not decoded, the peak luminance among them, are written back as they were
+    let mut written = block;
+    written[0] = 50;
+    written[5] = 0;
+    assert_eq!(reads(&requests), [0x0283_0000, 0x0283_0000]);
+    assert_eq!(
+        writes(&requests),
+        [
+            (0x0283_0000, written),
+            (0x0283_0005, vec![1]),
+            (0x0283_0004, vec![0]),
+        ]
+    );
+}
+
+#[test]
+fn sync_on_the_wire() {
+    use novastar_core::{Error, Feature, FieldRateMode, SyncSettings};
+
+    let settings = SyncSettings {
+        field_rate_mode: FieldRateMode::Custom,
+        field_rate: 59.94,
+        genlock_present: true,
+        low_latency: true,
+    };
+    let requests = record(
+        0x1107,
+        vec![
+            Reply::Data(vec![0x01]),
+            Reply::Data(5994u16.to_le_bytes().to_vec()),
+            Reply::Data(vec![1]),
+            Reply::Data(vec![1]),
+        ],
+        |controller| {
+            assert_eq!(controller.sync_settings().unwrap(), settings);
+            controller.set_sync_settings(&settings).unwrap();
+            for field_rate in [-1.0, f32::NAN, 655.36, f32::INFINITY] {
+                let invalid = SyncSettings {
+                    field_rate,
+                    ..settings
+                };
+                assert!(matches!(
+                    controller.set_sync_settings(&invalid),
+                    Err(Error::InvalidFieldRate(_))
+                ));
+            }
+        },
+    );
+    assert_eq!(
+        reads(&requests),
+        [0x0200_0008, 0x0200_0009, 0x0200_0020, 0x0200_0074]
+    );
+    assert_eq!(
+        writes(&requests),
+        [
+            (0x0200_0008, vec![0x01]),
+            (0x0200_0009, vec![0x6A, 0x17]),
+            (0x0200_0074, vec![1]),
+            (0x0200_01D5, vec![1]),
+        ]
+    );
+    assert_eq!(requests.last().unwrap().device, 1);
+
+    let requests = record(0x1101, vec![], |controller| {
+        let genlock = SyncSettings {
+            field_rate_mode: FieldRateMode::Genlock,
+            ..settings
+        };
+        assert!(matches!(
+            controller.set_sync_settings(&genlock),
+            Err(Error::Unsupported(Feature::Genlock))
+        ));
+    });
+    assert!(requests.is_empty());
+}
+
+#[test]
+fn mirror_rotation_and_3d_on_the_wire() {
+    use novastar_core::{CabinetRotation, Error, MirrorMode, ThreeDSettings};
+
+    let three_d = ThreeDSettings {
+        enabled: true,
+        right_eye_delay: 0x0123,
+    };
+    let requests = record(
+        0x1107,
+        vec![
+            Reply::Data(vec![0x02]),
+            Reply::Data(vec![0x03]),
+            Reply::Data(vec![1]),
+            Reply::Data(vec![0x23, 0x01]),
+            Reply::Data(vec![0x07]),
+        ],
+        |controller| {
+            assert_eq!(controller.mirror_mode().unwrap(), MirrorMode::Vertical);
+            assert_eq!(
+                controller.cabinet_rotation().unwrap(),
+                CabinetRotation::Rotate270
+            );
+            assert_eq!(controller.three_d_settings().unwrap(), three_d);
+            assert!(matches!(
+                controller.mirror_mode(),
+                Err(Error::UnexpectedValue(0x07))
+            ));
+
+            controller.set_mirror_mode(MirrorMode::Both).unwrap();
+            controller
+                .set_cabinet_rotation(CabinetRotation::Rotate90)
+                .unwrap();
+            controller.set_three_d_settings(&three_d).unwrap();
+        },
+    );
+    assert_eq!(
+        reads(&requests),
+        [
+            0x0200_003A,
+            0x0270_0000,
+            0x0200_0039,
+            0x0200_0041,
+            0x0200_003A
+        ]
+    );
+    assert_eq!(
+        writes(&requests),
+        [
+            (0x0200_003A, vec![0x03]),
+            (0x0200_0087, vec![0x03]),
+            (0x0270_0000, vec![0x01]),
+            (0x0200_0041, vec![0x23, 0x01]),
+            (0x0200_0039, vec![1]),
+        ]
+    );
+}
+
+#[test]
+fn clock_on_the_wire() {
+    use chrono::{Datelike, NaiveDate};
+    use novastar_core::Error;
+
+    let time = NaiveDate::from_ymd_opt(2024, 3, 9)
+        .unwrap()
+        .and_hms_opt(21, 45, 7)
+        .unwrap();
+    let requests = record(
+        0x0001,
+        vec![
+            Reply::Data(vec![24, 3, 9, 21, 45, 7]),
+            Reply::Data(vec![24, 2, 30, 0, 0, 0]),
+        ],
+        |controller| {
+            assert_eq!(controller.clock().unwrap(), time);
+            assert!(matches!(
+                controller.clock(),
+                Err(Error::InvalidClock(data)) if data == [24, 2, 30, 0, 0, 0]
+            ));
+            controller.set_clock(time).unwrap();
+            controller.sync_clock().unwrap();
+            for year in [1999, 2256] {
+                let time = time.with_year(year).unwrap();
+                assert!(matches!(
+                    controller.set_clock(time),
+                    Err(Error::InvalidClockYear(found)) if found == year
+                ));
+            }
+        },
+    );
+    assert_eq!(reads(&requests), [0x0200_0011, 0x0200_0011]);
+    let writes = writes(&requests);
+    assert_eq!(writes.len(), 2);
+    assert_eq!(writes[0], (0x0200_0011, vec![24, 3, 9, 21, 45, 7]));
+    assert_eq!(writes[1].0, 0x0200_0011);
+    assert_eq!(writes[1].1.len(), 6);
+}
+
+#[test]
+fn group_reports_each_member() {
+    use novastar_core::{Controller, ControllerGroup, Error, RetryPolicy};
+
+    let (first, first_stub) = controller_stub(0x0001, vec![vec![Reply::Data(vec![0x40])]]);
+    let (second, second_stub) = controller_stub(0x0001, vec![vec![]]);
+    let mut broken = Controller::try_from_tcp_addr(second).unwrap();
+    broken.set_retry_policy(RetryPolicy::none());
+    let group = ControllerGroup::new([Controller::try_from_tcp_addr(first).unwrap(), broken]);
+
+    let report = group.run(Controller::brightness);
+    assert_eq!(report.members.len(), 2);
+    assert_eq!(*report.members[0].result.as_ref().unwrap(), 0x40);
+    assert!(matches!(report.members[1].result, Err(Error::Read(_))));
+    assert!(!report.all_ok());
+    assert_eq!(report.successes().count(), 1);
+    assert_eq!(report.failures().count(), 1);
+    drop(group);
+    first_stub.join().unwrap();
+    second_stub.join().unwrap();
+}
+
+#[test]
+fn group_member_timeout() {
+    use std::time::Duration;
+
+    let (addr, stub) = controller_stub(0x0001, vec![vec![Reply::Hang(Duration::from_millis(300))]]);
+
+    let mut controller = novastar_core::Controller::try_from_tcp_addr(addr).unwrap();
+    controller.set_retry_policy(novastar_core::RetryPolicy::none());
+    let mut group = novastar_core::ControllerGroup::new([controller]);
+    group.set_timeout(Duration::from_millis(50));
+    let report = group.run(novastar_core::Controller::brightness);
+    assert!(matches!(
+        report.members[0].result,
+        Err(novastar_core::Error::Timeout(_))
+    ));
+    stub.join().unwrap();
+}
+
+#[test]
+fn group_member_panic() {
+    let (addr, stub) = controller_stub(0x0001, vec![vec![]]);
+    let group = novastar_core::ControllerGroup::new([
+        novastar_core::Controller::try_from_tcp_addr(addr).unwrap(),
+    ]);
+    let report = group.run(|_| -> Result<(), novastar_core::Error> { panic!("member panic") });
+    assert!(matches!(
+        report.members[0].result,
+        Err(novastar_core::Error::Panicked)
+    ));
+    drop(group);
+    stub.join().unwrap();
+}
+
+#[test]
+fn descriptor_checks_model() {
+    use novastar_core::{ControllerDescriptor, Error, Transport};
+
+    let (addr, stub) = controller_stub(0x0001, vec![vec![], vec![]]);
+    let descriptor = ControllerDescriptor {
+        transport: Transport::Tcp(addr),
+        model: SenderCardType::MCTRL300,
+    };
+    assert_eq!(descriptor.connect().unwrap().model_id(), 0x0001);
+    let moved = ControllerDescriptor {
+        model: SenderCardType::MCTRL500,
+        ..descriptor
+    };
+    assert!(matches!(
+        moved.connect(),
+        Err(Error::ModelChanged {
+            expected: 0x0101,
+            found: 0x0001
+        })
+    ));
+    stub.join().unwrap();
+}
+
+#[test]
+fn group_connect_partial_failure() {
+    use novastar_core::{ControllerDescriptor, ControllerGroup, Transport};
+
+    let (reachable, stub) = controller_stub(0x0001, vec![vec![]]);
+    let unreachable = std::net::TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap();
+    let descriptors = [reachable, unreachable].map(|addr| ControllerDescriptor {
+        transport: Transport::Tcp(addr),
+        model: SenderCardType::MCTRL300,
+    });
+
+    let (group, report) = ControllerGroup::connect(&descriptors);
+    assert_eq!(group.len(), 1);
+    assert_eq!(group.descriptors().next(), Some(&descriptors[0]));
+    assert!(report.members[0].result.is_ok());
+    assert!(matches!(
+        report.members[1].result,
+        Err(novastar_core::Error::Connection(_))
+    ));
+    drop(group);
+    stub.join().unwrap();
+}
+
+#[test]
+fn fade_curves() {
+    use novastar_core::FadeCurve;
+
+    for curve in [FadeCurve::Linear, FadeCurve::Ease, FadeCurve::Perceptual] {
+        assert_eq!(curve.brightness_at(10, 200, 0.0), 10);
+        assert_eq!(curve.brightness_at(10, 200, 1.0), 200);
+        assert_eq!(curve.brightness_at(200, 10, 2.0), 10);
+    }
+    assert_eq!(FadeCurve::Linear.brightness_at(0, 255, 0.5), 128);
+    assert_eq!(FadeCurve::Ease.brightness_at(0, 255, 0.5), 128);
+    assert_eq!(FadeCurve::Ease.brightness_at(0, 255, 0.1), 7);
+    assert_eq!(FadeCurve::Perceptual.brightness_at(0, 255, 0.5), 55);
+}
+
+#[test]
+fn fade_brightness() {
+    let (addr, stub) = controller_stub(0x0001, vec![vec![Reply::Data(vec![0])]]);
+    let mut controller = novastar_core::Controller::try_from_tcp_addr(addr).unwrap();
+    let reached = controller
+        .fade_brightness(
+            255,
+            std::time::Duration::from_millis(200),
+            novastar_core::FadeCurve::Linear,
+        )
+        .unwrap();
+    assert_eq!(reached, 255);
+    drop(controller);
+    let values: Vec<u8> = writes(&stub.join().unwrap())
+        .into_iter()
+        .map(|(_, data)| data[0])
+        .collect();
+    assert!(values.len() > 1);
+    assert!(values.windows(2).all(|pair| pair[0] < pair[1]));
+    assert_eq!(values.last(), Some(&255));
+}
+
+#[test]
+fn cancelled_fade() {
+    let (addr, stub) = controller_stub(0x0001, vec![vec![Reply::Data(vec![40])]]);
+    let mut controller = novastar_core::Controller::try_from_tcp_addr(addr).unwrap();
+    let cancel = novastar_core::CancelFade::new();
+    cancel.cancel();
+    let reached = controller
+        .fade_brightness_cancellable(
+            255,
+            std::time::Duration::from_secs(10),
+            novastar_core::FadeCurve::Ease,
+            &cancel,
+        )
+        .unwrap();
+    assert_eq!(reached, 40);
+    drop(controller);
+    assert!(writes(&stub.join().unwrap()).is_empty());
+}
+
+#[test]
+fn group_fade_in_lockstep() {
+    let stubs = [0, 100]
+        .map(|brightness| controller_stub(0x0001, vec![vec![Reply::Data(vec![brightness])]]));
+    let group = novastar_core::ControllerGroup::new(
+        stubs
+            .iter()
+            .map(|(addr, _)| novastar_core::Controller::try_from_tcp_addr(*addr).unwrap()),
+    );
+    let report = group.fade_brightness(
+        200,
+        std::time::Duration::from_millis(100),
+        novastar_core::FadeCurve::Perceptual,
+    );
+    assert!(report.all_ok());
+    assert!(report.successes().all(|(_, reached)| *reached == 200));
+    drop(group);
+    for (_, stub) in stubs {
+        let writes = writes(&stub.join().unwrap());
+        assert_eq!(writes.last(), Some(&(0x0200_0001, vec![200])));
+    }
+}
+
+fn artnet_dmx(universe: u16, channels: &[u8]) -> Vec<u8> {
+    let mut packet = b"Art-Net\0".to_vec();
+    packet.extend_from_slice(&0x5000u16.to_le_bytes());
+    packet.extend_from_slice(&14u16.to_be_bytes());
+    packet.extend_from_slice(&[0, 0]);
+    packet.extend_from_slice(&universe.to_le_bytes());
+    packet.extend_from_slice(&(channels.len() as u16).to_be_bytes());
+    packet.extend_from_slice(channels);
+    packet
+}
+
+fn sacn_dmx(universe: u16, options: u8, channels: &[u8]) -> Vec<u8> {
+    let mut packet = vec![0x00, 0x10, 0x00, 0x00];
+    packet.extend_from_slice(b"ASC-E1.17\0\0\0");
+    packet.extend_from_slice(&[0x70, 0x00, 0x00, 0x00, 0x00, 0x04]);
+    packet.extend_from_slice(&[0xCC; 16]);
+    packet.extend_from_slice(&[0x70, 0x00, 0x00, 0x00, 0x00, 0x02]);
+    packet.extend_from_slice(&[0; 64]);
+    packet.extend_from_slice(&[100, 0, 0, 0, options]);
+    packet.extend_from_slice(&universe.to_be_bytes());
+    packet.extend_from_slice(&[0x70, 0x00, 0x02, 0xA1, 0x00, 0x00, 0x00, 0x01]);
+    packet.extend_from_slice(&(channels.len() as u16 + 1).to_be_bytes());
+    packet.push(0x00);
+    packet.extend_from_slice(channels);
+    packet
+}
+
+#[test]
+fn parse_dmx_packets() {
+    use novastar_core::dmx::{DmxFrame, parse_artnet, parse_sacn};
+
+    let frame = DmxFrame {
+        universe: 0x0102,
+        channels: vec![1, 2, 3],
+    };
+    assert_eq!(
+        parse_artnet(&artnet_dmx(0x0102, &[1, 2, 3])),
+        Some(frame.clone())
+    );
+    assert_eq!(
+        parse_sacn(&sacn_dmx(0x0102, 0, &[1, 2, 3])),
+        Some(frame.clone())
+    );
+    assert_eq!(frame.channel(1), Some(1));
+    assert_eq!(frame.channel(0), None);
+    assert_eq!(frame.channel(4), None);
+
+    assert_eq!(parse_sacn(&sacn_dmx(1, 0x80, &[1])), None);
+    assert_eq!(parse_sacn(&sacn_dmx(1, 0x40, &[1])), None);
+    assert_eq!(parse_sacn(&artnet_dmx(1, &[1])), None);
+    assert_eq!(parse_artnet(&sacn_dmx(1, 0, &[1])), None);
+}
+
+#[test]
+fn artnet_bridge() {
+    use novastar_core::dmx::{DmxBridge, DmxMapping, DmxProtocol};
+    use std::time::Duration;
+
+    let (addr, stub) = controller_stub(0x0001, vec![vec![]]);
+    let mut controller = novastar_core::Controller::try_from_tcp_addr(addr).unwrap();
+    let mapping = DmxMapping {
+        brightness: Some(1),
+        rgb: Some(2),
+        test_pattern: Some(5),
+        ..DmxMapping::new(DmxProtocol::ArtNet, 3)
+    };
+    let mut bridge = DmxBridge::bind_to(mapping, "127.0.0.1:0".parse().unwrap()).unwrap();
+    bridge.set_rate_limit(Duration::from_millis(200)).unwrap();
+    let desk = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+    let bridge_addr = bridge.local_addr().unwrap();
+
+    desk.send_to(&artnet_dmx(3, &[128, 255, 200, 100, 25]), bridge_addr)
+        .unwrap();
+    assert!(bridge.poll(&mut controller).unwrap());
+    // Another universe is ignored, a quick update waits for the rate limit
+    desk.send_to(&artnet_dmx(4, &[0, 0, 0, 0, 0]), bridge_addr)
+        .unwrap();
+    assert!(!bridge.poll(&mut controller).unwrap());
+    desk.send_to(&artnet_dmx(3, &[129, 255, 200, 100, 25]), bridge_addr)
+        .unwrap();
+    assert!(!bridge.poll(&mut controller).unwrap());
+    std::thread::sleep(Duration::from_millis(200));
+    assert!(bridge.poll(&mut controller).unwrap());
+    assert!(!bridge.poll(&mut controller).unwrap());
+
+    drop(controller);
+    assert_eq!(
+        writes(&stub.join().unwrap()),
+        vec![
+            (0x0100_0003, vec![0x03]),
+            (0x0200_0001, vec![128]),
+            (0x0200_0002, vec![255, 200, 100]),
+            (0x0200_0001, vec![129]),
+        ]
+    );
+}
+
+#[test]
+fn sacn_bridge_blackout() {
+    use novastar_core::dmx::{DmxBridge, DmxMapping, DmxProtocol};
+
+    let (addr, stub) = controller_stub(0x0001, vec![vec![]]);
+    let mut controller = novastar_core::Controller::try_from_tcp_addr(addr).unwrap();
+    let mapping = DmxMapping {
+        blackout: Some(10),
+        ..DmxMapping::new(DmxProtocol::Sacn, 1)
+    };
+    let mut bridge = DmxBridge::bind_to(mapping, "127.0.0.1:0".parse().unwrap()).unwrap();
+    let desk = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+
+    let mut channels = [0; 10];
+    channels[9] = 255;
+    desk.send_to(&sacn_dmx(1, 0, &channels), bridge.local_addr().unwrap())
+        .unwrap();
+    assert!(bridge.poll(&mut controller).unwrap());
+
+    drop(controller);
+    let writes = writes(&stub.join().unwrap());
+    assert_eq!(writes.len(), 3);
+    assert_eq!(
+        writes[0],
+        (
+            0x0100_0000,
+            vec![novastar_core::DisplayState::Blackout.into()]
+        )
+    );
+}
+
+#[test]
+fn retry_policy_backoff() {
+    use std::time::Duration;
+
+    let policy = novastar_core::RetryPolicy::default();
+    assert_eq!(policy.delay(1), Duration::from_millis(100));
+    assert_eq!(policy.delay(2), Duration::from_millis(200));
+    assert_eq!(policy.delay(5), Duration::from_millis(1600));
+    assert_eq!(policy.delay(6), Duration::from_secs(2));
+    assert_eq!(policy.delay(40), Duration::from_secs(2));
+}
+
+#[test]
+fn custom_edid_round_trip() {
+    let edid = novastar_core::Edid::custom(1920, 1080, 60).unwrap();
+    let bytes = edid.to_bytes();
+    assert_eq!(
+        bytes.iter().take(128).fold(0u8, |s, b| s.wrapping_add(*b)),
+        0
+    );
+    assert_eq!(
+        bytes.iter().skip(128).fold(0u8, |s, b| s.wrapping_add(*b)),
+        0
+    );
+
+    let parsed = novastar_core::Edid::parse(&bytes).unwrap();
+    assert_eq!(parsed, edid);
+    assert_eq!(parsed.manufacturer, "NVS");
+    let preferred = parsed.preferred_timing.unwrap();
+    assert_eq!(
+        preferred.mode(),
+        novastar_core::VideoMode::new(1920, 1080, 60)
+    );
+}
+
+#[test]
+fn parse_edid_modes() {
+    let mut edid = novastar_core::Edid::custom(3840, 2160, 30).unwrap();
+    edid.supported_modes = vec![
+        novastar_core::VideoMode::new(800, 600, 60),
+        novastar_core::VideoMode::new(1920, 1080, 60),
+        novastar_core::VideoMode::new(1280, 1024, 60),
+    ];
+    edid.cea_extension.as_mut().unwrap().video_codes = vec![16, 97];
+
+    let parsed = novastar_core::Edid::parse(&edid.to_bytes()).unwrap();
+    assert_eq!(parsed.supported_modes, edid.supported_modes);
+    assert_eq!(parsed.cea_extension.unwrap().video_codes, vec![16, 97]);
+}
+
+#[test]
+fn parse_edid_without_cea_timings() {
+    let mut bytes = novastar_core::Edid::custom(1920, 1080, 60)
+        .unwrap()
+        .to_bytes();
+    assert!(
+        !novastar_core::Edid::parse(&bytes)
+            .unwrap()
+            .cea_extension
+            .unwrap()
+            .detailed_timings
+            .is_empty()
+    );
+
+    // An offset of 0 leaves the rest of the extension block unused
+    bytes[130] = 0;
+    bytes[255] = bytes[128..255]
+        .iter()
+        .fold(0u8, |sum, byte| sum.wrapping_add(*byte))
+        .wrapping_neg();
+    let cea = novastar_core::Edid::parse(&bytes)
+        .unwrap()
+        .cea_extension
+        .unwrap();
+    assert!(cea.detailed_timings.is_empty());
+    assert!(cea.video_codes.is_empty());
+}
+
+#[test]
+fn refresh_rate_of_degenerate_timings() {
+    let mut timing = novastar_core::Edid::custom(1920, 1080, 60)
+        .unwrap()
+        .preferred_timing
+        .unwrap();
+    timing.h_active = u16::MAX;
+    timing.h_blanking = u16::MAX;
+    assert!(timing.refresh_rate() > 0.0);
+    timing.v_active = 0;
+    timing.v_blanking = 0;
+    assert_eq!(timing.refresh_rate(), 0.0);
+    assert_eq!(timing.mode().refresh_rate, 0);
+}
+
+#[test]
+fn parse_edid_rejects_corrupted_blob() {
+    let mut bytes = novastar_core::Edid::custom(1920, 1080, 60)
+        .unwrap()
+        .to_bytes();
+    bytes[20] ^= 0xFF;
+    assert_eq!(
+        novastar_core::Edid::parse(&bytes),
+        Err(novastar_core::EdidError::Checksum(0))
+    );
+    assert_eq!(
+        novastar_core::Edid::parse(&bytes[..64]),
+        Err(novastar_core::EdidError::Length(64))
+    );
+}
+
+/// Serves one canned JSON response per request and returns the requested paths and bodies
+#[cfg(feature = "h-series")]
+fn h_series_stub(
+    responses: Vec<(u16, &'static str)>,
+) -> (String, std::thread::JoinHandle<Vec<(String, String)>>) {
+    use std::io::{BufRead, BufReader, Read, Write};
+
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let host = listener.local_addr().unwrap().to_string();
+    let handle = std::thread::spawn(move || {
+        let mut requests = Vec::new();
+        for (status, body) in responses {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream);
+            let mut request_line = String::new();
+            reader.read_line(&mut request_line).unwrap();
+            let mut content_length = 0;
+            loop {
+                let mut header = String::new();
+                reader.read_line(&mut header).unwrap();
+                if header.trim().is_empty() {
+                    break;
+                }
+                if let Some((name, value)) = header.split_once(':')
+                    && name.eq_ignore_ascii_case("content-length")
+                {
+                    content_length = value.trim().parse().unwrap();
+                }
+            }
+            let mut request_body = vec![0; content_length];
+            reader.read_exact(&mut request_body).unwrap();
+            let path = request_line.split_whitespace().nth(1).unwrap().to_string();
+            requests.push((path, String::from_utf8(request_body).unwrap()));
+
+            write!(
+                reader.get_mut(),
+                "HTTP/1.1 {status} OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                body.len()
+            )
+            .unwrap();
+        }
+        requests
+    });
+    (host, handle)
+}
+
+#[cfg(feature = "h-series")]
+#[test]
+fn h_series_set_brightness() {
+    let (host, stub) = h_series_stub(vec![(200, r#"{"status":0,"msg":"ok"}"#)]);
+    novastar_core::h_series::HSeriesClient::new(&host)
+        .set_brightness(0, 46)
+        .unwrap();
+    let requests = stub.join().unwrap();
+    assert_eq!(requests.len(), 1);
+    assert_eq!(requests[0].0, "/api/screen/writeBrightness");
+    assert_eq!(
+        serde_json::from_str::<serde_json::Value>(&requests[0].1).unwrap(),
+        serde_json::json!({"brightness": 46, "screenId": 0, "deviceId": 0})
+    );
+}
+
+#[cfg(feature = "h-series")]
+#[test]
+fn h_series_screens_and_device_info() {
+    let (host, stub) = h_series_stub(vec![
+        (
+            200,
+            r#"{"status":0,"msg":"","data":[{"screenId":0,"name":"Main","brightness":80},{"screenId":1,"name":"Side","brightness":60}]}"#,
+        ),
+        (
+            200,
+            r#"{"status":0,"msg":"","data":{"deviceId":0,"name":"H9","modelId":31558,"serialNumber":"H9-0042","firmwareVersion":"1.2.0"}}"#,
+        ),
+    ]);
+    let client = novastar_core::h_series::HSeriesClient::new(&host);
+    let screens = client.screens().unwrap();
+    assert_eq!(screens.len(), 2);
+    assert_eq!(screens[1].name, "Side");
+    assert_eq!(screens[1].brightness, 60);
+
+    let info = client.device_info().unwrap();
+    assert_eq!(info.model_id, 0x7b46);
+    assert_eq!(info.serial_number, "H9-0042");
+    stub.join().unwrap();
+}
+
+#[cfg(feature = "h-series")]
+#[test]
+fn h_series_errors() {
+    use novastar_core::h_series::{HSeriesClient, HSeriesError};
+
+    let (host, stub) = h_series_stub(vec![
+        (200, r#"{"status":3,"msg":"screen does not exist"}"#),
+        (500, r#"{}"#),
+        (200, r#"{"status":0,"msg":"ok"}"#),
+    ]);
+    let client = HSeriesClient::new(&host);
+    assert!(matches!(
+        client.set_brightness(7, 50),
+        Err(HSeriesError::Api { status: 3, message }) if message == "screen does not exist"
+    ));
+    assert!(matches!(client.screens(), Err(HSeriesError::Status(500))));
+    assert!(matches!(
+        client.screens(),
+        Err(HSeriesError::MissingData(path)) if path == "screen/readAllList"
+    ));
+    stub.join().unwrap();
+}
+
+#[cfg(feature = "h-series")]
+#[test]
+fn h_series_presets_and_inputs() {
+    let (host, stub) = h_series_stub(vec![
+        (
+            200,
+            r#"{"status":0,"data":[{"presetId":1,"name":"Opening"},{"presetId":2,"name":"Keynote"}]}"#,
+        ),
+        (200, r#"{"status":0}"#),
+        (
+            200,
+            r#"{"status":0,"data":[{"inputId":0,"name":"HDMI 1","hasSignal":true,"width":1920,"height":1080,"refreshRate":59.94},{"inputId":1,"name":"SDI 1","hasSignal":false}]}"#,
+        ),
+    ]);
+    let client = novastar_core::h_series::HSeriesClient::new(&host);
+    let presets = client.presets(0).unwrap();
+    assert_eq!(presets[1].name, "Keynote");
+    client.load_preset(0, presets[1].preset_id).unwrap();
+
+    let inputs = client.inputs().unwrap();
+    assert!(inputs[0].has_signal);
+    assert_eq!((inputs[0].width, inputs[0].height), (1920, 1080));
+    assert!(!inputs[1].has_signal);
+
+    let requests = stub.join().unwrap();
+    assert_eq!(requests[1].0, "/api/preset/load");
+    assert_eq!(
+        serde_json::from_str::<serde_json::Value>(&requests[1].1).unwrap(),
+        serde_json::json!({"presetId": 2, "screenId": 0, "deviceId": 0})
+    );
+}
+
+#[cfg(feature = "h-series")]
+#[test]
+fn h_series_layers() {
+    use novastar_core::h_series::{HSeriesClient, HSeriesError, Window};
+
+    let layers = r#"{"status":0,"data":[{"layerId":4,"inputId":0,"window":{"x":0,"y":0,"width":960,"height":540}}]}"#;
+    let (host, stub) = h_series_stub(vec![
+        (200, r#"{"status":0,"data":{"layerId":4}}"#),
+        (200, layers),
+        (200, r#"{"status":0}"#),
+        (200, layers),
+    ]);
+    let client = HSeriesClient::new(&host);
+    let window = Window {
+        x: 0,
+        y: 0,
+        width: 960,
+        height: 540,
+    };
+    assert_eq!(client.create_layer(0, 0, window).unwrap(), 4);
+    client.move_layer(0, 4, 960, 540).unwrap();
+    assert!(matches!(
+        client.resize_layer(0, 5, 100, 100),
+        Err(HSeriesError::UnknownLayer(5))
+    ));
+
+    let requests = stub.join().unwrap();
+    assert_eq!(requests[2].0, "/api/layer/writeWindow");
+    assert_eq!(
+        serde_json::from_str::<serde_json::Value>(&requests[2].1).unwrap()["window"],
+        serde_json::json!({"x": 960, "y": 540, "width": 960, "height": 540})
+    );
+}
+
+/// Runs the command-line tool with `args`
+#[cfg(feature = "cli")]
+fn novastar(args: &[&str]) -> std::process::Output {
+    std::process::Command::new(env!("CARGO_BIN_EXE_novastar"))
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+#[cfg(feature = "cli")]
+#[test]
+fn cli_rejects_invalid_arguments() {
+    let stderr =
+        |output: &std::process::Output| String::from_utf8_lossy(&output.stderr).to_string();
+
+    // Rejected by the argument parser
+    for args in [
+        &["read", "127.0.0.1", "0xZZ"][..],
+        &["test-pattern", "127.0.0.1", "purple"],
+        &["test-pattern", "127.0.0.1", "red", "--card", "1"],
+        &["write", "127.0.0.1", "0x02000001"],
+    ] {
+        let output = novastar(args);
+        assert_eq!(
+            output.status.code(),
+            Some(2),
+            "{args:?}: {}",
+            stderr(&output)
+        );
+    }
+    assert!(
+        stderr(&novastar(&["test-pattern", "127.0.0.1", "purple"])).contains("expected one of")
+    );
+
+    // Rejected before connecting, without panicking on multi-byte characters
+    for (data, message) in [
+        ("\u{e9}0", "invalid hexadecimal digits"),
+        ("0\u{e9}", "invalid hexadecimal digits"),
+        ("abc", "odd number of hexadecimal digits"),
+        ("zz", "invalid hexadecimal byte"),
+    ] {
+        let output = novastar(&["write", "127.0.0.1:1", "0x02000001", data]);
+        assert_eq!(
+            output.status.code(),
+            Some(1),
+            "{data:?}: {}",
+            stderr(&output)
+        );
+        assert!(
+            stderr(&output).contains(message),
+            "{data:?}: {}",
+            stderr(&output)
+        );
+    }
+}
+
+#[cfg(feature = "cli")]
+#[test]
+fn cli_write_and_read_registers() {
+    let (addr, stub) = controller_stub(0x0001, vec![vec![], vec![Reply::Data(vec![0x12, 0x34])]]);
+    let addr = addr.to_string();
+
+    let output = novastar(&["write", &addr, "0x02000001", "0x01", "0Xff"]);
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "wrote 2 bytes at 0x02000001\n"
+    );
+
+    let output = novastar(&["--json", "read", &addr, "33554433", "2"]);
+    assert!(output.status.success());
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(
+        json,
+        serde_json::json!({ "address": 0x0200_0001, "data": [0x12, 0x34] })
+    );
+
+    let requests = stub.join().unwrap();
+    assert_eq!(writes(&requests), [(0x0200_0001, vec![0x01, 0xff])]);
+    assert_eq!(requests[1].address, 0x0200_0001);
+}
+
+#[cfg(feature = "cli")]
+#[test]
+fn cli_sets_brightness_without_reading_it_back() {
+    let (addr, stub) = controller_stub(0x0001, vec![vec![]]);
+
+    let output = novastar(&["brightness", &addr.to_string(), "50"]);
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "50\n");
+
+    let requests = stub.join().unwrap();
+    assert_eq!(writes(&requests), [(0x0200_0001, vec![50])]);
+    assert_eq!(requests.len(), 1);
+}
+
+/// The web API is not built in, so only the model of an H Series controller is known
+#[cfg(all(feature = "cli", not(feature = "h-series")))]
+#[test]
+fn cli_info_skips_unsupported_queries() {
+    let (addr, stub) = controller_stub(0x7b46, vec![vec![]]);
+
+    let output = novastar(&["--json", "info", &addr.to_string()]);
+    assert!(output.status.success());
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(json["model_id"], "0x7b46");
+    assert!(json["serial_number"].is_null());
+    assert!(json["brightness"].is_null());
+    assert!(json["display_state"].is_null());
+    assert!(stub.join().unwrap().is_empty());
+}
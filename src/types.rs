@@ -1,110 +1,309 @@
-use num_enum::{IntoPrimitive, TryFromPrimitive};
-use strum_macros::Display;
+use num_enum::{FromPrimitive, IntoPrimitive, TryFromPrimitive};
+use std::str::FromStr;
+use strum::IntoEnumIterator;
+use strum_macros::{Display, EnumIter};
+use thiserror::Error;
 
 #[derive(PartialEq, Clone, Copy, Debug, TryFromPrimitive, IntoPrimitive)]
 #[repr(u32)]
 pub enum FeatureAddress {
     GlobalBrightnessOccupancy = 1,
     ControllerModelIdAddr = 0x0000_0002,
+    ControllerSnHighAddr = 0x0000_0016,
+    SaveSendCardsParametersAddr = 0x0100_0001,
+    ReturnFactoryValuesAddr = 0x0100_0002,
+    TestPointAddr = 0x0100_0003,
+    ParameterReloadFromSpiFlashAddr = 0x0100_0010,
+    ParameterStore2SpiFlashAddr = 0x0100_0011,
     GlobalBrightnessAddr = 0x0200_0001,
+    FieldRateModeAddr = 0x0200_0008,
+    SetFieldRateAddr = 0x0200_0009,
+    RtcoAddr = 0x0200_0011,
+    MasterOrSlaveAddr = 0x0200_0018,
+    IsHasGenLockAddr = 0x0200_0020,
+    Enable3DAddr = 0x0200_0039,
+    MirrorModeFirAddr = 0x0200_003A,
+    ThreeDPerAddr = 0x0200_0041,
+    LowDelayAddr = 0x0200_0074,
+    MirrorModeSecAddr = 0x0200_0087,
+    MasterOrSlaveNewAddr = 0x0200_0098,
+    MasterOrSlaveDeviceStateAddr = 0x0200_009D,
+    ShowNormalAddr = 0x0200_00EE,
+    MasterOrSlaveNew16Addr = 0x0200_0100,
     GammaAddr = 0x200_019B,
+    ScannerLowDelayAddr = 0x0200_01D5,
+    MasterOrSlaveNew32Addr = 0x0201_0040,
+    SenderVideoScalingModeAddr = 0x0220_0000,
+    SenderVideoScreenOffsetAddr = 0x0220_0001,
+    SenderVideoInputCutAddr = 0x0220_0005,
+    SenderVideoWindowRectAddr = 0x0220_000D,
+    SenderVideoMosaicAddr = 0x0220_0015,
+    HDRInfoAddr = 0x0220_0117,
+    HLGModelAddr = 0x0220_0129,
+    HDR10MinLum = 0x0220_012E,
+    SenderBlackScreenSleepAddr = 0x0220_0140,
+    SenderHardWareBackupInfoAddr = 0x0230_0000,
+    CabinetRotationAddr = 0x0270_0000,
+    SenderABLtionAddr = 0x0283_0000,
+    SenderEDEOpreationAddr = 0x0283_0004,
+    SenderABLOpreationAddr = 0x0283_0005,
+    ScrenPeakLumAddr = 0x0283_0006,
+    SenderEdidSpaceAddr = 0x0800_0000,
     SenderGlobalBrightnessArrayAddr = 0x0A00_00A0,
+    SetBackUpOrRestoreAddr = 0x1A00_0000,
+    BackUpFinishFlagAddr = 0x1A00_0001,
+    RestoreFinishFlagAddr = 0x1A00_0002,
+    BackUpFileHeaderAddr = 0x1A00_0100,
+    BackUpFileAddr = 0x1A00_0101,
+    LedParamLenAddr = 0x1A00_0200,
+    LedParamInfoAddr = 0x1A00_0204,
 }
 
+/// Registers of the receiving cards missing from the reference table at the end of this file
+///
+/// The table lists the sending card registers, where the brightness addresses hold
+/// `TemperatureAddr` and `HumidityAddr` instead. On the receiving cards they are offsets in
+/// `Scanner_parameterBase`, right after the global brightness at offset 1. No published register
+/// map of the receiving cards confirms them, so they are kept apart from [FeatureAddress] and never
+/// used to name sending card registers
+#[derive(PartialEq, Clone, Copy, Debug, TryFromPrimitive, IntoPrimitive)]
+#[allow(clippy::enum_variant_names)]
+#[repr(u32)]
+pub enum ScannerAddress {
+    /// What the receiving cards show, as a [DisplayState]
+    ///
+    /// The table only lists `Sender_cmdBase` and `Scanner_cmdBase` at this address. The `0`
+    /// normal, `1` freeze and `2` blackout encoding of the mode is assumed rather than documented
+    DisplayModeAddr = 0x0100_0000,
+    RedBrightnessAddr = 0x0200_0002,
+    GreenBrightnessAddr = 0x0200_0003,
+    BlueBrightnessAddr = 0x0200_0004,
+}
+
+/// Operation of a [crate::NovastarPacket]
 #[derive(PartialEq, Clone, Copy, Debug, TryFromPrimitive, IntoPrimitive)]
 #[repr(u8)]
 pub enum OpCode {
+    /// Reads a register
     Read = 0,
+    /// Writes a register
     Write = 1,
 }
 
+/// Kind of card a [crate::NovastarPacket] is meant for
 #[derive(PartialEq, Clone, Copy, Debug, TryFromPrimitive, IntoPrimitive)]
 #[repr(u8)]
 pub enum DeviceType {
+    /// The sending card of the controller
     Controller,
+    /// The receiving cards behind the output ports
     Scanboard,
 }
 
-#[allow(non_camel_case_types)]
+/// What the LED screen is currently showing
 #[derive(PartialEq, Clone, Copy, Debug, TryFromPrimitive, IntoPrimitive, Display)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[repr(u8)]
+pub enum DisplayState {
+    /// The input video is displayed
+    Normal = 0x00,
+    /// The last displayed frame is held on screen
+    Freeze = 0x01,
+    /// All LEDs are turned off
+    Blackout = 0x02,
+}
+
+/// Patterns generated by the receiving cards themselves, without any video source
+#[derive(PartialEq, Clone, Copy, Debug, TryFromPrimitive, IntoPrimitive, Display)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[repr(u8)]
+pub enum TestPattern {
+    /// No test pattern, the input video is displayed
+    Normal = 0x00,
+    /// Solid red
+    Red = 0x02,
+    /// Solid green
+    Green = 0x03,
+    /// Solid blue
+    Blue = 0x04,
+    /// Solid white
+    White = 0x05,
+    /// Moving horizontal lines
+    HorizontalLines = 0x06,
+    /// Moving vertical lines
+    VerticalLines = 0x07,
+    /// Moving diagonal lines
+    DiagonalLines = 0x08,
+    /// Grayscale gradient
+    Gradient = 0x09,
+    /// Grid of one pixel wide lines
+    Grid = 0x0A,
+    /// Cycles through every pattern
+    Aging = 0x0B,
+}
+
+/// Model of a sending card, from the model ID it reports
+#[allow(non_camel_case_types)]
+#[derive(PartialEq, Clone, Copy, Debug, FromPrimitive, IntoPrimitive, Display, EnumIter)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(u16)]
 pub enum SenderCardType {
+    /// MCTRL300, model ID 0x0001
     MCTRL300 = 0x0001,
+    /// MCTRL500, model ID 0x0101
     MCTRL500 = 0x0101,
+    /// MCTRL600/660, model ID 0x1101
     #[strum(to_string = "MCTRL600/660")]
     MCTRL600_660 = 0x1101,
+    /// MCTRL4K, model ID 0x1103
     #[strum(to_string = "MCTRL4K")]
     MCTRL4K = 0x1103,
+    /// MCTRL660 Pro, model ID 0x1107
     #[strum(to_string = "MCTRL660 Pro")]
     MCTRL660_Pro = 0x1107,
+    /// MCTRL660 ROE, model ID 0x1108
     #[strum(to_string = "MCTRL660 ROE")]
     MCTRL660_ROE = 0x1108,
+    /// MCTRL2000, model ID 0x1115
     MCTRL2000 = 0x1115,
+    /// GT4000, model ID 0x1180
     GT4000 = 0x1180,
+    /// MP8-JF, model ID 0x1307
     #[strum(to_string = "MP8-JF")]
     MP8_JF = 0x1307,
+    /// E510, model ID 0x1308
     E510 = 0x1308,
+    /// KT16_ZM, model ID 0x130c
     KT16_ZM = 0x130C,
+    /// FTSC4000-E, model ID 0x130e
     #[strum(to_string = "FTSC4000-E")]
     FTSC4000_E = 0x130E,
+    /// Empty, model ID 0x2101
     Empty = 0x2101,
+    /// K4S-N, model ID 0x6129
     #[strum(to_string = "K4S-N")]
     K4S_N = 0x6129,
+    /// VX4S-N, model ID 0x612a
     #[strum(to_string = "VX4S-N")]
     VX4S_N = 0x612A,
+    /// NovaPro UHD Jr, model ID 0x6205
     #[strum(to_string = "NovaPro UHD Jr")]
     NovaProUHD_Jr = 0x6205,
+    /// 4K-Prime, model ID 0x6209
     #[strum(to_string = "4K-Prime")]
     Prime_4K = 0x6209,
+    /// DS-D43V32, model ID 0x6210
     #[strum(to_string = "DS-D43V32")]
     DS_D43V32 = 0x6210,
+    /// VC6 Pro, model ID 0x6227
     #[strum(to_string = "VC6 Pro")]
     VC6Pro = 0x6227,
+    /// VC10 Pro, model ID 0x6228
     #[strum(to_string = "VC10 Pro")]
     VC10Pro = 0x6228,
+    /// VX400 Pro, model ID 0x6229
     #[strum(to_string = "VX400 Pro")]
     VX400Pro = 0x6229,
+    /// VX600 Pro, model ID 0x622a
     #[strum(to_string = "VX600 Pro")]
     VX600Pro = 0x622A,
+    /// VX1000 Pro, model ID 0x622b
     #[strum(to_string = "VX1000 Pro")]
     VX1000Pro = 0x622B,
+    /// VX2000 Pro, model ID 0x622c
     #[strum(to_string = "VX2000 Pro")]
     VX2000Pro = 0x622C,
+    /// VX200s-N, model ID 0x622d
     #[strum(to_string = "VX200s-N")]
     VX200s_N = 0x622D,
+    /// J6, model ID 0x7104
     J6 = 0x7104,
+    /// DP 4K 1-3, model ID 0x7204
     #[strum(to_string = "DP 4K 1-3")]
     DP_4K_1_3 = 0x7204,
+    /// NovaPro UHD, model ID 0x7504
     #[strum(to_string = "NovaPro UHD")]
     NovaProUHD = 0x7504,
-    #[strum(to_string = "E8000")]
+    /// E8000, model ID 0x7505
+    #[strum(to_string = "E8000 (0x7505)")]
     E8000_1 = 0x7505,
-    #[strum(to_string = "E8000")]
+    /// E8000, model ID 0x7647
+    #[strum(to_string = "E8000 (0x7647)")]
     E8000_2 = 0x7647,
-    #[strum(to_string = "E8000")]
+    /// E8000, model ID 0x7648
+    #[strum(to_string = "E8000 (0x7648)")]
     E8000_3 = 0x7648,
-    #[strum(to_string = "H Series")]
+    /// H Series (H9), model ID 0x7b46
+    #[strum(to_string = "H Series (H9)")]
     H_series_H9 = 0x7b46,
-    #[strum(to_string = "H Series")]
+    /// H Series (H20), model ID 0x7b48
+    #[strum(to_string = "H Series (H20)")]
     H_series_H20 = 0x7b48,
+    /// Planar Series, model ID 0x8101
     #[strum(to_string = "Planar Series")]
     Planar = 0x8101,
-    #[strum(to_string = "Planar Series")]
+    /// Planar Series (H20), model ID 0x9048
+    #[strum(to_string = "Planar Series (H20)")]
     H20_Planar = 0x9048,
+    /// MCTRL700, model ID 0x9101
     MCTRL700 = 0x9101,
+    /// DS-D40T02-N, model ID 0x9622
     #[strum(to_string = "DS-D40T02-N")]
     DS_D40T02_N = 0x9622,
+    /// DS-D43T02-N, model ID 0x9623
     #[strum(to_string = "DS-D43T02-N")]
     DS_D43T02_N = 0x9623,
+    /// DS-D40T04-N, model ID 0x9624
     #[strum(to_string = "DS-D40T04-N")]
     DS_D40T04_N = 0x9624,
+    /// DS-D43T04-N, model ID 0x9625
     #[strum(to_string = "DS-D43T04-N")]
     DS_D43T04_N = 0x9625,
+    /// DS-D40T01, model ID 0x9626
     #[strum(to_string = "DS-D40T01")]
     DS_D40T01 = 0x9626,
+    /// DS-D43T01, model ID 0x9627
     #[strum(to_string = "DS-D43T01")]
     DS_D43T01 = 0x9627,
+    /// Model ID 0xffff, reported when the model could not be read
     Unknown = 0xFFFF,
+    /// Any other model ID, for models this crate does not know
+    #[num_enum(catch_all)]
+    #[strum(to_string = "Unknown ({0:#06x})")]
+    Other(u16) = 0x0000,
+}
+
+/// Error returned when parsing a [SenderCardType] from a name it does not have
+#[derive(Error, Debug, PartialEq, Eq)]
+#[error("unknown controller model {0:?}")]
+pub struct UnknownModel(pub String);
+
+impl SenderCardType {
+    /// Returns the model whose displayed name or variant name is `name`, ignoring case
+    pub fn from_name(name: &str) -> Option<Self> {
+        let name = name.trim();
+        Self::iter()
+            .filter(|model| !matches!(model, SenderCardType::Other(_)))
+            .find(|model| {
+                model.to_string().eq_ignore_ascii_case(name)
+                    || format!("{model:?}").eq_ignore_ascii_case(name)
+            })
+    }
+}
+
+impl FromStr for SenderCardType {
+    type Err = UnknownModel;
+
+    /// Parses a model name, or a raw model ID in hexadecimal such as `0x1101`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if let Some(hex) = s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+            return u16::from_str_radix(hex, 16)
+                .map(Self::from_primitive)
+                .map_err(|_| UnknownModel(s.to_string()));
+        }
+        Self::from_name(s).ok_or_else(|| UnknownModel(s.to_string()))
+    }
 }
 
 /*
@@ -0,0 +1,98 @@
+use num_enum::{IntoPrimitive, TryFromPrimitive};
+
+/// How the input video is fitted on the LED screen
+#[derive(PartialEq, Eq, Clone, Copy, Debug, TryFromPrimitive, IntoPrimitive)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[repr(u8)]
+pub enum ScalingMode {
+    /// Each input pixel is shown on one LED pixel
+    PixelToPixel = 0x00,
+    /// The input is stretched to fill the whole screen
+    FullScreen = 0x01,
+    /// The input crop is scaled into the output window
+    Custom = 0x02,
+}
+
+/// Rectangle in pixels, from its top left corner
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Rect {
+    /// Horizontal position of the left edge
+    pub x: u16,
+    /// Vertical position of the top edge
+    pub y: u16,
+    /// Width
+    pub width: u16,
+    /// Height
+    pub height: u16,
+}
+
+impl Rect {
+    pub(crate) fn decode(data: &[u8]) -> Self {
+        Self {
+            x: u16::from_le_bytes([data[0], data[1]]),
+            y: u16::from_le_bytes([data[2], data[3]]),
+            width: u16::from_le_bytes([data[4], data[5]]),
+            height: u16::from_le_bytes([data[6], data[7]]),
+        }
+    }
+
+    pub(crate) fn encode(&self) -> [u8; 8] {
+        let mut data = [0; 8];
+        data[0..2].copy_from_slice(&self.x.to_le_bytes());
+        data[2..4].copy_from_slice(&self.y.to_le_bytes());
+        data[4..6].copy_from_slice(&self.width.to_le_bytes());
+        data[6..8].copy_from_slice(&self.height.to_le_bytes());
+        data
+    }
+}
+
+/// Pixelation of an area of the output, with square blocks
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Mosaic {
+    /// The mosaic is shown
+    pub enabled: bool,
+    /// Area covered by the mosaic
+    pub area: Rect,
+    /// Width of a mosaic block in pixels
+    pub block_width: u16,
+    /// Height of a mosaic block in pixels
+    pub block_height: u16,
+}
+
+impl Mosaic {
+    pub(crate) fn decode(data: &[u8]) -> Self {
+        Self {
+            enabled: data[0] != 0,
+            area: Rect::decode(&data[1..9]),
+            block_width: u16::from_le_bytes([data[9], data[10]]),
+            block_height: u16::from_le_bytes([data[11], data[12]]),
+        }
+    }
+
+    pub(crate) fn encode(&self) -> [u8; 13] {
+        let mut data = [0; 13];
+        data[0] = self.enabled as u8;
+        data[1..9].copy_from_slice(&self.area.encode());
+        data[9..11].copy_from_slice(&self.block_width.to_le_bytes());
+        data[11..13].copy_from_slice(&self.block_height.to_le_bytes());
+        data
+    }
+}
+
+/// Video processing settings of a [crate::Controller]
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct VideoProcessing {
+    /// How the input is fitted on the screen
+    pub scaling_mode: ScalingMode,
+    /// Position of the output on the screen, as `(x, y)`
+    pub screen_offset: (u16, u16),
+    /// Part of the input which is displayed
+    pub input_crop: Rect,
+    /// Part of the screen the cropped input is scaled into
+    pub output_window: Rect,
+    /// Mosaic shown over the output
+    pub mosaic: Mosaic,
+}